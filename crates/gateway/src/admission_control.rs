@@ -0,0 +1,217 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::http::HeaderMap;
+use starknet_api::core::ContractAddress;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::config::{AdmissionControlConfig, RateLimitConfig};
+use crate::errors::{GatewayError, GatewayResult};
+
+#[cfg(test)]
+#[path = "admission_control_test.rs"]
+mod admission_control_test;
+
+const X_FORWARDED_FOR: &str = "x-forwarded-for";
+
+// The minimal number of tracked clients from which a rate limiter prunes its idle clients.
+const MIN_PRUNE_THRESHOLD: usize = 1024;
+
+/// Admits transactions for validation, so that blocking validations don't exhaust the runtime:
+/// clients are rate limited, a bounded number of validations run concurrently, and the rest wait
+/// in a bounded queue, from which they are shed if the gateway is overloaded.
+#[derive(Debug)]
+pub struct AdmissionControl {
+    config: AdmissionControlConfig,
+    validation_permits: Arc<Semaphore>,
+    n_queued_validations: AtomicUsize,
+    client_ip_rate_limiter: Option<RateLimiter<IpAddr>>,
+    sender_address_rate_limiter: Option<RateLimiter<ContractAddress>>,
+}
+
+impl AdmissionControl {
+    pub fn new(config: AdmissionControlConfig) -> Self {
+        AdmissionControl {
+            validation_permits: Arc::new(Semaphore::new(config.max_concurrent_validations)),
+            n_queued_validations: AtomicUsize::new(0),
+            client_ip_rate_limiter: config.client_ip_rate_limit.map(RateLimiter::new),
+            sender_address_rate_limiter: config.sender_address_rate_limit.map(RateLimiter::new),
+            config,
+        }
+    }
+
+    /// Returns the IP of the client that sent a request, given the IP of the connected peer, if
+    /// known, and the request's headers. A request forwarded by trusted proxies is attributed to
+    /// the rightmost address of its `X-Forwarded-For` header that is not of a trusted proxy;
+    /// addresses to its left are set by the client, and are thus ignored.
+    pub fn client_ip(&self, peer_ip: Option<IpAddr>, headers: &HeaderMap) -> Option<IpAddr> {
+        let mut client_ip = peer_ip?;
+        let forwarded_ips: Vec<&str> = headers
+            .get_all(X_FORWARDED_FOR)
+            .iter()
+            .flat_map(|header_value| header_value.to_str().unwrap_or_default().split(','))
+            .collect();
+
+        for forwarded_ip in forwarded_ips.into_iter().rev() {
+            if !self.config.trusted_proxies.contains(&client_ip) {
+                break;
+            }
+            match forwarded_ip.trim().parse() {
+                Ok(forwarded_ip) => client_ip = forwarded_ip,
+                // The request is attributed to the last proxy that reported it validly.
+                Err(_) => break,
+            }
+        }
+        Some(client_ip)
+    }
+
+    pub fn check_client_ip(&self, client_ip: IpAddr) -> GatewayResult<()> {
+        check_rate_limit(self.client_ip_rate_limiter.as_ref(), client_ip)
+    }
+
+    pub fn check_sender_address(&self, sender_address: ContractAddress) -> GatewayResult<()> {
+        check_rate_limit(self.sender_address_rate_limiter.as_ref(), sender_address)
+    }
+
+    /// Waits for a validation slot, held until the returned permit is dropped. Fails if the queue
+    /// of waiting validations is full, or if the wait exceeds its limit.
+    pub async fn acquire_validation_permit(&self) -> GatewayResult<OwnedSemaphorePermit> {
+        if let Ok(permit) = self.validation_permits.clone().try_acquire_owned() {
+            return Ok(permit);
+        }
+
+        let _queue_slot = self.enter_queue()?;
+        match tokio::time::timeout(
+            self.config.max_queue_wait,
+            self.validation_permits.clone().acquire_owned(),
+        )
+        .await
+        {
+            Ok(permit) => Ok(permit.expect("The validation semaphore is never closed.")),
+            Err(_elapsed) => Err(self.overloaded()),
+        }
+    }
+
+    fn enter_queue(&self) -> GatewayResult<QueueSlot<'_>> {
+        let n_queued_validations = self.n_queued_validations.fetch_add(1, Ordering::SeqCst);
+        // Created before the check, to leave the queue on rejection as well.
+        let queue_slot = QueueSlot(&self.n_queued_validations);
+        if n_queued_validations >= self.config.max_queued_validations {
+            return Err(self.overloaded());
+        }
+
+        Ok(queue_slot)
+    }
+
+    fn overloaded(&self) -> GatewayError {
+        GatewayError::Overloaded { retry_after: self.config.overload_retry_after }
+    }
+}
+
+fn check_rate_limit<K: Eq + Hash>(
+    rate_limiter: Option<&RateLimiter<K>>,
+    key: K,
+) -> GatewayResult<()> {
+    match rate_limiter {
+        Some(rate_limiter) => rate_limiter
+            .try_acquire(key, Instant::now())
+            .map_err(|retry_after| GatewayError::RateLimited { retry_after }),
+        None => Ok(()),
+    }
+}
+
+/// A place in the queue of validations, left when dropped (including when the waiting request is
+/// cancelled).
+struct QueueSlot<'a>(&'a AtomicUsize);
+
+impl Drop for QueueSlot<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Rate limits requests by key, with a token bucket per key.
+#[derive(Debug)]
+pub struct RateLimiter<K> {
+    config: RateLimitConfig,
+    state: Mutex<RateLimiterState<K>>,
+}
+
+#[derive(Debug)]
+struct RateLimiterState<K> {
+    buckets: HashMap<K, TokenBucket>,
+    // The number of tracked keys above which idle keys are pruned.
+    prune_threshold: usize,
+}
+
+impl<K: Eq + Hash> RateLimiter<K> {
+    pub fn new(config: RateLimitConfig) -> Self {
+        RateLimiter {
+            config,
+            state: Mutex::new(RateLimiterState {
+                buckets: HashMap::new(),
+                prune_threshold: MIN_PRUNE_THRESHOLD,
+            }),
+        }
+    }
+
+    /// Consumes a request of the given key, or returns the time after which it may be retried.
+    pub fn try_acquire(&self, key: K, now: Instant) -> Result<(), Duration> {
+        let mut state = self.state.lock().expect("The rate limiter's lock should not be poisoned.");
+
+        // Keys whose buckets are full behave as new ones, so they need not be tracked. The
+        // threshold grows with the number of remaining keys, to keep pruning amortized.
+        if state.buckets.len() >= state.prune_threshold {
+            state.buckets.retain(|_, bucket| !bucket.is_full(&self.config, now));
+            state.prune_threshold = MIN_PRUNE_THRESHOLD.max(2 * state.buckets.len());
+        }
+
+        state
+            .buckets
+            .entry(key)
+            .or_insert_with(|| TokenBucket::new(&self.config, now))
+            .try_acquire(&self.config, now)
+    }
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(config: &RateLimitConfig, now: Instant) -> Self {
+        TokenBucket { tokens: f64::from(config.burst_size), last_refill: now }
+    }
+
+    fn try_acquire(&mut self, config: &RateLimitConfig, now: Instant) -> Result<(), Duration> {
+        self.refill(config, now);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            return Ok(());
+        }
+
+        let missing_tokens = 1.0 - self.tokens;
+        Err(Duration::from_secs_f64(missing_tokens / f64::from(config.requests_per_second)))
+    }
+
+    fn is_full(&self, config: &RateLimitConfig, now: Instant) -> bool {
+        self.tokens_at(config, now) >= f64::from(config.burst_size)
+    }
+
+    fn refill(&mut self, config: &RateLimitConfig, now: Instant) {
+        self.tokens = self.tokens_at(config, now);
+        self.last_refill = self.last_refill.max(now);
+    }
+
+    fn tokens_at(&self, config: &RateLimitConfig, now: Instant) -> f64 {
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        let refilled_tokens = elapsed.as_secs_f64() * f64::from(config.requests_per_second);
+        (self.tokens + refilled_tokens).min(f64::from(config.burst_size))
+    }
+}
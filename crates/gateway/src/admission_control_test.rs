@@ -0,0 +1,146 @@
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+
+use assert_matches::assert_matches;
+use axum::http::{HeaderMap, HeaderValue};
+use pretty_assertions::assert_eq;
+use rstest::rstest;
+use starknet_api::core::ContractAddress;
+
+use crate::admission_control::{AdmissionControl, RateLimiter};
+use crate::config::{AdmissionControlConfig, RateLimitConfig};
+use crate::errors::GatewayError;
+
+const RATE_LIMIT_CONFIG: RateLimitConfig =
+    RateLimitConfig { burst_size: 2, requests_per_second: 4 };
+
+#[test]
+fn test_rate_limiter_burst_and_refill() {
+    let rate_limiter = RateLimiter::new(RATE_LIMIT_CONFIG);
+    let start = Instant::now();
+
+    // The burst is admitted at once.
+    assert_eq!(rate_limiter.try_acquire("a", start), Ok(()));
+    assert_eq!(rate_limiter.try_acquire("a", start), Ok(()));
+    assert_eq!(rate_limiter.try_acquire("a", start), Err(Duration::from_millis(250)));
+
+    // Keys are limited independently.
+    assert_eq!(rate_limiter.try_acquire("b", start), Ok(()));
+
+    // Requests are replenished over time.
+    let later = start + Duration::from_millis(100);
+    assert_eq!(rate_limiter.try_acquire("a", later), Err(Duration::from_millis(150)));
+    let later = start + Duration::from_millis(250);
+    assert_eq!(rate_limiter.try_acquire("a", later), Ok(()));
+    assert_eq!(rate_limiter.try_acquire("a", later), Err(Duration::from_millis(250)));
+
+    // Replenishment is capped by the burst size.
+    let much_later = start + Duration::from_secs(60);
+    assert_eq!(rate_limiter.try_acquire("a", much_later), Ok(()));
+    assert_eq!(rate_limiter.try_acquire("a", much_later), Ok(()));
+    assert!(rate_limiter.try_acquire("a", much_later).is_err());
+}
+
+#[test]
+fn test_rate_limits_are_disabled_by_default() {
+    let admission_control = AdmissionControl::new(AdmissionControlConfig::default());
+
+    for _ in 0..100 {
+        assert_matches!(admission_control.check_client_ip(IpAddr::from([127, 0, 0, 1])), Ok(()));
+        assert_matches!(admission_control.check_sender_address(ContractAddress::default()), Ok(()));
+    }
+}
+
+#[test]
+fn test_rate_limited_sender_address() {
+    let admission_control = AdmissionControl::new(AdmissionControlConfig {
+        sender_address_rate_limit: Some(RATE_LIMIT_CONFIG),
+        ..Default::default()
+    });
+    let sender_address = ContractAddress::default();
+
+    assert_matches!(admission_control.check_sender_address(sender_address), Ok(()));
+    assert_matches!(admission_control.check_sender_address(sender_address), Ok(()));
+    assert_matches!(
+        admission_control.check_sender_address(sender_address),
+        Err(GatewayError::RateLimited { .. })
+    );
+    // The client IP is not limited.
+    assert_matches!(admission_control.check_client_ip(IpAddr::from([127, 0, 0, 1])), Ok(()));
+}
+
+#[tokio::test]
+async fn test_validations_beyond_queue_are_shed() {
+    let admission_control = AdmissionControl::new(AdmissionControlConfig {
+        max_concurrent_validations: 1,
+        max_queued_validations: 0,
+        ..Default::default()
+    });
+
+    let permit = admission_control.acquire_validation_permit().await.unwrap();
+    assert_matches!(
+        admission_control.acquire_validation_permit().await,
+        Err(GatewayError::Overloaded { retry_after }) if retry_after == Duration::from_secs(1)
+    );
+
+    // Once the validation is done, the next one is admitted.
+    drop(permit);
+    assert_matches!(admission_control.acquire_validation_permit().await, Ok(_));
+}
+
+#[tokio::test]
+async fn test_queued_validation_wait() {
+    let admission_control = AdmissionControl::new(AdmissionControlConfig {
+        max_concurrent_validations: 1,
+        max_queued_validations: 1,
+        max_queue_wait: Duration::from_millis(10),
+        ..Default::default()
+    });
+
+    // A queued validation that waits too long is shed.
+    let permit = admission_control.acquire_validation_permit().await.unwrap();
+    assert_matches!(
+        admission_control.acquire_validation_permit().await,
+        Err(GatewayError::Overloaded { .. })
+    );
+
+    // A queued validation is admitted once a validation is done.
+    let (queued_result, ()) =
+        tokio::join!(admission_control.acquire_validation_permit(), async { drop(permit) });
+    assert_matches!(queued_result, Ok(_));
+}
+
+#[rstest]
+#[case::direct_client(None, "2.2.2.2", "2.2.2.2")]
+#[case::untrusted_proxy(Some("1.1.1.1"), "2.2.2.2", "2.2.2.2")]
+#[case::trusted_proxy(Some("1.1.1.1"), "10.0.0.1", "1.1.1.1")]
+#[case::spoofed_addresses(Some("3.3.3.3, 1.1.1.1"), "10.0.0.1", "1.1.1.1")]
+#[case::chain_of_trusted_proxies(Some("1.1.1.1, 10.0.0.2"), "10.0.0.1", "1.1.1.1")]
+#[case::all_trusted_proxies(Some("10.0.0.2"), "10.0.0.1", "10.0.0.2")]
+#[case::missing_header(None, "10.0.0.1", "10.0.0.1")]
+#[case::invalid_address(Some("1.1.1.1, unknown"), "10.0.0.1", "10.0.0.1")]
+fn test_client_ip(
+    #[case] forwarded_for: Option<&str>,
+    #[case] peer_ip: &str,
+    #[case] expected_client_ip: &str,
+) {
+    let admission_control = AdmissionControl::new(AdmissionControlConfig {
+        trusted_proxies: vec![IpAddr::from([10, 0, 0, 1]), IpAddr::from([10, 0, 0, 2])],
+        ..Default::default()
+    });
+    let mut headers = HeaderMap::new();
+    if let Some(forwarded_for) = forwarded_for {
+        headers.insert("x-forwarded-for", HeaderValue::from_str(forwarded_for).unwrap());
+    }
+
+    assert_eq!(
+        admission_control.client_ip(Some(peer_ip.parse().unwrap()), &headers),
+        Some(expected_client_ip.parse().unwrap())
+    );
+}
+
+#[test]
+fn test_client_ip_of_unknown_peer() {
+    let admission_control = AdmissionControl::new(AdmissionControlConfig::default());
+    assert_eq!(admission_control.client_ip(None, &HeaderMap::new()), None);
+}
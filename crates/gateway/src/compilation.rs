@@ -8,6 +8,7 @@ use cairo_lang_starknet_classes::casm_contract_class::{
 use cairo_lang_starknet_classes::contract_class::ContractClass as CairoLangContractClass;
 use starknet_api::core::CompiledClassHash;
 use starknet_api::rpc_transaction::RPCDeclareTransaction;
+use starknet_mempool_types::mempool_types::CompiledClassInfo;
 use starknet_sierra_compile::compile::compile_sierra_to_casm;
 use starknet_sierra_compile::errors::CompilationUtilError;
 use starknet_sierra_compile::utils::into_contract_class_for_compilation;
@@ -35,6 +36,15 @@ impl GatewayCompiler {
         &self,
         declare_tx: &RPCDeclareTransaction,
     ) -> GatewayResult<ClassInfo> {
+        to_class_info(&self.compile_declare_tx(declare_tx)?)
+    }
+
+    /// Formats the contract class for compilation, compiles it, and returns the compiled contract
+    /// class, as passed on to the mempool.
+    pub fn compile_declare_tx(
+        &self,
+        declare_tx: &RPCDeclareTransaction,
+    ) -> GatewayResult<CompiledClassInfo> {
         let RPCDeclareTransaction::V3(tx) = declare_tx;
         let rpc_contract_class = &tx.contract_class;
         let cairo_lang_contract_class = into_contract_class_for_compilation(rpc_contract_class);
@@ -44,11 +54,11 @@ impl GatewayCompiler {
         validate_compiled_class_hash(&casm_contract_class, &tx.compiled_class_hash)?;
         validate_casm_class(&casm_contract_class)?;
 
-        Ok(ClassInfo::new(
-            &ContractClass::V1(ContractClassV1::try_from(casm_contract_class)?),
-            rpc_contract_class.sierra_program.len(),
-            rpc_contract_class.abi.len(),
-        )?)
+        Ok(CompiledClassInfo {
+            casm_contract_class,
+            sierra_program_length: rpc_contract_class.sierra_program.len(),
+            abi_length: rpc_contract_class.abi.len(),
+        })
     }
 
     // TODO(Arni): Pass the compilation args from the config.
@@ -65,6 +75,28 @@ impl GatewayCompiler {
     }
 }
 
+/// Builds the [`ClassInfo`] executed by the blockifier from a compiled class.
+/// The blockifier's conversion takes ownership of the Casm class; only the parts it executes are
+/// copied for it, leaving out the Python hints and the compiler version.
+pub fn to_class_info(compiled_class_info: &CompiledClassInfo) -> GatewayResult<ClassInfo> {
+    let CompiledClassInfo { casm_contract_class, sierra_program_length, abi_length } =
+        compiled_class_info;
+    let executable_casm_contract_class = CasmContractClass {
+        prime: casm_contract_class.prime.clone(),
+        compiler_version: String::new(),
+        bytecode: casm_contract_class.bytecode.clone(),
+        bytecode_segment_lengths: casm_contract_class.bytecode_segment_lengths.clone(),
+        hints: casm_contract_class.hints.clone(),
+        pythonic_hints: None,
+        entry_points_by_type: casm_contract_class.entry_points_by_type.clone(),
+    };
+    Ok(ClassInfo::new(
+        &ContractClass::V1(ContractClassV1::try_from(executable_casm_contract_class)?),
+        *sierra_program_length,
+        *abi_length,
+    )?)
+}
+
 // TODO(Arni): Add test.
 fn validate_casm_class(contract_class: &CasmContractClass) -> Result<(), GatewayError> {
     let CasmContractEntryPoints { external, l1_handler, constructor } =
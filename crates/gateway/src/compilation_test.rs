@@ -1,5 +1,5 @@
 use assert_matches::assert_matches;
-use blockifier::execution::contract_class::ContractClass;
+use blockifier::execution::contract_class::{ContractClass, ContractClassV1};
 use cairo_lang_starknet_classes::allowed_libfuncs::AllowedLibfuncsError;
 use mempool_test_utils::starknet_api_test_utils::declare_tx;
 use rstest::{fixture, rstest};
@@ -7,7 +7,7 @@ use starknet_api::core::CompiledClassHash;
 use starknet_api::rpc_transaction::{RPCDeclareTransaction, RPCTransaction};
 use starknet_sierra_compile::errors::CompilationUtilError;
 
-use crate::compilation::GatewayCompiler;
+use crate::compilation::{to_class_info, GatewayCompiler};
 use crate::errors::GatewayError;
 
 #[fixture]
@@ -68,3 +68,28 @@ fn test_process_declare_tx_success(gateway_compiler: GatewayCompiler) {
     assert_eq!(class_info.sierra_program_length(), contract_class.sierra_program.len());
     assert_eq!(class_info.abi_length(), contract_class.abi.len());
 }
+
+#[rstest]
+fn test_compile_declare_tx_success(gateway_compiler: GatewayCompiler) {
+    let declare_tx = assert_matches!(
+        declare_tx(),
+        RPCTransaction::Declare(declare_tx) => declare_tx
+    );
+    let RPCDeclareTransaction::V3(declare_tx_v3) = &declare_tx;
+    let contract_class = &declare_tx_v3.contract_class;
+
+    let compiled_class_info = gateway_compiler.compile_declare_tx(&declare_tx).unwrap();
+    assert_eq!(
+        CompiledClassHash(compiled_class_info.casm_contract_class.compiled_class_hash()),
+        declare_tx_v3.compiled_class_hash
+    );
+    assert_eq!(compiled_class_info.sierra_program_length, contract_class.sierra_program.len());
+    assert_eq!(compiled_class_info.abi_length, contract_class.abi.len());
+
+    // The compiled class is convertible to the class info executed by the blockifier; the parts
+    // left out of the conversion do not affect the executed class.
+    let class_info = to_class_info(&compiled_class_info).unwrap();
+    let contract_class_v1 =
+        ContractClassV1::try_from(compiled_class_info.casm_contract_class).unwrap();
+    assert_eq!(class_info.contract_class(), ContractClass::V1(contract_class_v1));
+}
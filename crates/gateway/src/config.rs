@@ -1,10 +1,17 @@
 use std::collections::BTreeMap;
 use std::net::IpAddr;
+use std::time::Duration;
 
 use blockifier::context::{BlockContext, ChainInfo, FeeTokenAddresses};
-use papyrus_config::dumping::{append_sub_config_name, ser_param, SerializeConfig};
+use papyrus_config::converters::{
+    deserialize_milliseconds_to_duration, deserialize_seconds_to_duration,
+};
+use papyrus_config::dumping::{
+    append_sub_config_name, ser_optional_sub_config, ser_param, SerializeConfig,
+};
 use papyrus_config::{ParamPath, ParamPrivacyInput, SerializedParam};
-use serde::{Deserialize, Serialize};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize};
 use starknet_api::core::{ChainId, ContractAddress, Nonce};
 use starknet_types_core::felt::Felt;
 use validator::Validate;
@@ -17,6 +24,8 @@ pub struct GatewayConfig {
     pub stateless_tx_validator_config: StatelessTransactionValidatorConfig,
     pub stateful_tx_validator_config: StatefulTransactionValidatorConfig,
     pub compiler_config: GatewayCompilerConfig,
+    #[validate]
+    pub admission_control_config: AdmissionControlConfig,
 }
 
 impl SerializeConfig for GatewayConfig {
@@ -32,6 +41,10 @@ impl SerializeConfig for GatewayConfig {
                 "stateful_tx_validator_config",
             ),
             append_sub_config_name(self.compiler_config.dump(), "compiler_config"),
+            append_sub_config_name(
+                self.admission_control_config.dump(),
+                "admission_control_config",
+            ),
         ]
         .into_iter()
         .flatten()
@@ -304,3 +317,147 @@ impl SerializeConfig for GatewayCompilerConfig {
         BTreeMap::new()
     }
 }
+
+/// The configuration of the gateway's admission control, which protects transaction validation
+/// from overload: validations are bounded and queued, and clients are rate limited.
+#[derive(Clone, Debug, Serialize, Deserialize, Validate, PartialEq)]
+pub struct AdmissionControlConfig {
+    // The maximal number of transactions validated concurrently.
+    #[validate(range(min = 1))]
+    pub max_concurrent_validations: usize,
+    // The maximal number of transactions waiting for validation; further transactions are
+    // rejected.
+    pub max_queued_validations: usize,
+    // The maximal time a transaction waits for validation before it is rejected.
+    #[serde(deserialize_with = "deserialize_milliseconds_to_duration")]
+    pub max_queue_wait: Duration,
+    // The time after which clients of rejected transactions are advised to retry, when the
+    // gateway is overloaded.
+    #[serde(deserialize_with = "deserialize_seconds_to_duration")]
+    pub overload_retry_after: Duration,
+    // Limits the rate of transactions from each client IP; disabled if not provided.
+    #[validate]
+    pub client_ip_rate_limit: Option<RateLimitConfig>,
+    // Limits the rate of transactions from each sender address; disabled if not provided.
+    #[validate]
+    pub sender_address_rate_limit: Option<RateLimitConfig>,
+    // The IPs of the proxies trusted to report the client IP, in the `X-Forwarded-For` header of
+    // the requests they forward.
+    #[serde(deserialize_with = "deserialize_ip_addrs")]
+    pub trusted_proxies: Vec<IpAddr>,
+}
+
+impl Default for AdmissionControlConfig {
+    fn default() -> Self {
+        AdmissionControlConfig {
+            max_concurrent_validations: 32,
+            max_queued_validations: 1024,
+            max_queue_wait: Duration::from_millis(1000),
+            overload_retry_after: Duration::from_secs(1),
+            client_ip_rate_limit: None,
+            sender_address_rate_limit: None,
+            trusted_proxies: Vec::new(),
+        }
+    }
+}
+
+impl SerializeConfig for AdmissionControlConfig {
+    fn dump(&self) -> BTreeMap<ParamPath, SerializedParam> {
+        let params = BTreeMap::from_iter([
+            ser_param(
+                "max_concurrent_validations",
+                &self.max_concurrent_validations,
+                "Maximal number of transactions validated concurrently.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "max_queued_validations",
+                &self.max_queued_validations,
+                "Maximal number of transactions waiting for validation; further transactions are \
+                 rejected.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "max_queue_wait",
+                &self.max_queue_wait.as_millis(),
+                "Maximal time, in milliseconds, a transaction waits for validation before it is \
+                 rejected.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "overload_retry_after",
+                &self.overload_retry_after.as_secs(),
+                "Time, in seconds, after which clients of rejected transactions are advised to \
+                 retry, when the gateway is overloaded.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "trusted_proxies",
+                &serialize_ip_addrs(&self.trusted_proxies),
+                "Space-separated IPs of the proxies trusted to report the client IP, in the \
+                 X-Forwarded-For header of the requests they forward.",
+                ParamPrivacyInput::Public,
+            ),
+        ]);
+
+        params
+            .into_iter()
+            .chain(ser_optional_sub_config(&self.client_ip_rate_limit, "client_ip_rate_limit"))
+            .chain(ser_optional_sub_config(
+                &self.sender_address_rate_limit,
+                "sender_address_rate_limit",
+            ))
+            .collect()
+    }
+}
+
+/// Serializes IPs to a space-separated string.
+fn serialize_ip_addrs(ip_addrs: &[IpAddr]) -> String {
+    ip_addrs.iter().map(IpAddr::to_string).collect::<Vec<_>>().join(" ")
+}
+
+/// Deserializes IPs from a space-separated string.
+fn deserialize_ip_addrs<'de, D>(de: D) -> Result<Vec<IpAddr>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let raw_str: String = Deserialize::deserialize(de)?;
+    raw_str.split_whitespace().map(|ip_addr| ip_addr.parse().map_err(D::Error::custom)).collect()
+}
+
+/// The configuration of a token bucket rate limit: each client may send a burst of requests, after
+/// which its requests are replenished at a constant rate.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Validate, PartialEq)]
+pub struct RateLimitConfig {
+    // The maximal number of requests a client may send in a burst.
+    #[validate(range(min = 1))]
+    pub burst_size: u32,
+    // The number of requests replenished per second.
+    #[validate(range(min = 1))]
+    pub requests_per_second: u32,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        RateLimitConfig { burst_size: 20, requests_per_second: 10 }
+    }
+}
+
+impl SerializeConfig for RateLimitConfig {
+    fn dump(&self) -> BTreeMap<ParamPath, SerializedParam> {
+        BTreeMap::from_iter([
+            ser_param(
+                "burst_size",
+                &self.burst_size,
+                "Maximal number of requests a client may send in a burst.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "requests_per_second",
+                &self.requests_per_second,
+                "Number of requests replenished per second.",
+                ParamPrivacyInput::Public,
+            ),
+        ])
+    }
+}
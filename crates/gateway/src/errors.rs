@@ -1,20 +1,34 @@
-use axum::http::StatusCode;
+use std::time::Duration;
+
+use axum::http::header::RETRY_AFTER;
+use axum::http::{HeaderValue, StatusCode};
 use axum::response::{IntoResponse, Response};
+use axum::Json;
 use blockifier::blockifier::stateful_validator::StatefulValidatorError;
 use blockifier::execution::errors::ContractClassError;
 use blockifier::state::errors::StateError;
-use blockifier::transaction::errors::TransactionExecutionError;
+use blockifier::transaction::errors::{
+    TransactionExecutionError, TransactionFeeError, TransactionPreValidationError,
+};
 use cairo_vm::types::errors::program_errors::ProgramError;
-use serde_json::{Error as SerdeError, Value};
+use serde_json::{json, Error as SerdeError, Value};
 use starknet_api::block::GasPrice;
 use starknet_api::core::CompiledClassHash;
 use starknet_api::transaction::{Resource, ResourceBounds};
 use starknet_api::StarknetApiError;
+use starknet_mempool_types::communication::MempoolClientError;
+use starknet_mempool_types::errors::MempoolError;
 use starknet_sierra_compile::errors::CompilationUtilError;
 use thiserror::Error;
 use tokio::task::JoinError;
+use tracing::error;
 
 use crate::compiler_version::{VersionId, VersionIdError};
+use crate::rpc_objects::RpcSpecError;
+
+#[cfg(test)]
+#[path = "errors_test.rs"]
+mod errors_test;
 
 /// Errors directed towards the end-user, as a result of gateway requests.
 #[derive(Debug, Error)]
@@ -32,8 +46,12 @@ pub enum GatewayError {
     DeclaredContractProgramError(#[from] ProgramError),
     #[error("Internal server error: {0}")]
     InternalServerError(#[from] JoinError),
-    #[error("Error sending message: {0}")]
-    MessageSendError(String),
+    #[error(transparent)]
+    MempoolClientError(#[from] MempoolClientError),
+    #[error("The gateway is overloaded; retry after {retry_after:?}.")]
+    Overloaded { retry_after: Duration },
+    #[error("Too many transactions were sent; retry after {retry_after:?}.")]
+    RateLimited { retry_after: Duration },
     #[error(transparent)]
     StatefulTransactionValidatorError(#[from] StatefulTransactionValidatorError),
     #[error(transparent)]
@@ -44,12 +62,239 @@ pub enum GatewayError {
 
 pub type GatewayResult<T> = Result<T, GatewayError>;
 
+impl GatewayError {
+    /// Returns the spec error describing this error, along with the HTTP status code of its
+    /// response: a client error (4xx) if the request should be fixed, and a server error (5xx)
+    /// otherwise.
+    pub fn spec_error(&self) -> (StatusCode, GatewaySpecError) {
+        match self {
+            GatewayError::CompilationError(_)
+            | GatewayError::DeclaredContractClassError(_)
+            | GatewayError::DeclaredContractProgramError(_)
+            | GatewayError::UnsupportedBuiltins { .. } => {
+                (StatusCode::BAD_REQUEST, GatewaySpecError::CompilationFailed)
+            }
+            GatewayError::CompiledClassHashMismatch { .. } => {
+                (StatusCode::BAD_REQUEST, GatewaySpecError::CompiledClassHashMismatch)
+            }
+            GatewayError::InternalServerError(_) => {
+                (StatusCode::INTERNAL_SERVER_ERROR, GatewaySpecError::UnexpectedError)
+            }
+            GatewayError::MempoolClientError(MempoolClientError::MempoolError(error)) => {
+                mempool_spec_error(error)
+            }
+            GatewayError::MempoolClientError(MempoolClientError::ClientError(_)) => {
+                (StatusCode::INTERNAL_SERVER_ERROR, GatewaySpecError::UnexpectedError)
+            }
+            GatewayError::Overloaded { .. } => {
+                (StatusCode::SERVICE_UNAVAILABLE, GatewaySpecError::UnexpectedError)
+            }
+            GatewayError::RateLimited { .. } => {
+                (StatusCode::TOO_MANY_REQUESTS, GatewaySpecError::UnexpectedError)
+            }
+            GatewayError::StatefulTransactionValidatorError(error) => error.spec_error(),
+            GatewayError::StatelessTransactionValidatorError(error) => {
+                (StatusCode::BAD_REQUEST, error.spec_error())
+            }
+        }
+    }
+
+    /// Returns the time after which the request may be retried, if the error is transient.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            GatewayError::Overloaded { retry_after }
+            | GatewayError::RateLimited { retry_after } => Some(*retry_after),
+            _ => None,
+        }
+    }
+
+    /// Returns the spec error object describing this error, along with the HTTP status code of
+    /// its response. Server errors are logged rather than detailed in the object, so as not to
+    /// expose the gateway's internals.
+    pub fn rpc_spec_error(&self) -> (StatusCode, RpcSpecError) {
+        let (status_code, spec_error) = self.spec_error();
+        if status_code.is_server_error() {
+            error!("Failed to serve a request: {self}");
+        }
+        let rpc_spec_error = RpcSpecError {
+            code: spec_error.code(),
+            message: spec_error.message().to_string(),
+            data: self.data(status_code),
+        };
+        (status_code, rpc_spec_error)
+    }
+
+    fn data(&self, status_code: StatusCode) -> Option<Value> {
+        if let Some(retry_after) = self.retry_after() {
+            return Some(json!({ "retry_after": retry_after_secs(retry_after) }));
+        }
+        if status_code.is_server_error() {
+            return None;
+        }
+
+        match self {
+            GatewayError::CompiledClassHashMismatch { supplied, hash_result } => {
+                Some(json!({ "supplied": supplied, "hash_result": hash_result }))
+            }
+            GatewayError::MempoolClientError(MempoolClientError::MempoolError(error)) => {
+                mempool_error_data(error)
+            }
+            GatewayError::StatelessTransactionValidatorError(error) => Some(error.data()),
+            GatewayError::UnsupportedBuiltins { builtins, supported_builtins } => {
+                Some(json!({ "builtins": builtins, "supported_builtins": supported_builtins }))
+            }
+            _ => Some(Value::String(self.to_string())),
+        }
+    }
+}
+
 impl IntoResponse for GatewayError {
-    // TODO(Arni, 1/5/2024): Be more fine tuned about the error response. Not all Gateway errors
-    // are internal server errors.
+    /// Responds with a Starknet spec error object. Transient errors also set the `Retry-After`
+    /// header.
     fn into_response(self) -> Response {
-        let body = self.to_string();
-        (StatusCode::INTERNAL_SERVER_ERROR, body).into_response()
+        let (status_code, body) = self.rpc_spec_error();
+        let mut response = (status_code, Json(body)).into_response();
+        if let Some(retry_after) = self.retry_after() {
+            response.headers_mut().insert(RETRY_AFTER, retry_after_header_value(retry_after));
+        }
+        response
+    }
+}
+
+/// Returns the value of the `Retry-After` header for the given retry delay.
+pub fn retry_after_header_value(retry_after: Duration) -> HeaderValue {
+    HeaderValue::from(retry_after_secs(retry_after))
+}
+
+/// Returns the given retry delay in whole seconds, rounded up so that clients don't retry too
+/// early.
+fn retry_after_secs(retry_after: Duration) -> u64 {
+    retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0)
+}
+
+/// The errors of the Starknet spec's API returned by the gateway.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum GatewaySpecError {
+    CompilationFailed,
+    CompiledClassHashMismatch,
+    ContractClassSizeIsTooLarge,
+    DuplicateTx,
+    InsufficientAccountBalance,
+    InsufficientFeeEscalation,
+    InsufficientMaxFee,
+    InvalidTransactionNonce,
+    TxnHashNotFound,
+    UnexpectedError,
+    UnsupportedContractClassVersion,
+    ValidationFailure,
+}
+
+impl GatewaySpecError {
+    pub fn code(&self) -> u16 {
+        match self {
+            GatewaySpecError::TxnHashNotFound => 29,
+            GatewaySpecError::InvalidTransactionNonce => 52,
+            GatewaySpecError::InsufficientMaxFee => 53,
+            GatewaySpecError::InsufficientAccountBalance => 54,
+            GatewaySpecError::ValidationFailure => 55,
+            GatewaySpecError::CompilationFailed => 56,
+            GatewaySpecError::ContractClassSizeIsTooLarge => 57,
+            GatewaySpecError::DuplicateTx => 59,
+            GatewaySpecError::CompiledClassHashMismatch => 60,
+            GatewaySpecError::UnsupportedContractClassVersion => 62,
+            GatewaySpecError::UnexpectedError => 63,
+            // The spec has no dedicated error for replacing a transaction with insufficient fees;
+            // its code is outside the spec's range, so that clients can tell the two apart.
+            GatewaySpecError::InsufficientFeeEscalation => 1000,
+        }
+    }
+
+    pub fn message(&self) -> &'static str {
+        match self {
+            GatewaySpecError::CompilationFailed => "Compilation failed",
+            GatewaySpecError::CompiledClassHashMismatch => {
+                "the compiled class hash did not match the one supplied in the transaction"
+            }
+            GatewaySpecError::ContractClassSizeIsTooLarge => "Contract class size is too large",
+            GatewaySpecError::DuplicateTx => {
+                "A transaction with the same hash already exists in the mempool"
+            }
+            GatewaySpecError::InsufficientAccountBalance => {
+                "Account balance is smaller than the transaction's max_fee"
+            }
+            GatewaySpecError::InsufficientFeeEscalation => {
+                "The fees of the transaction do not sufficiently exceed those of the transaction \
+                 it replaces"
+            }
+            GatewaySpecError::InsufficientMaxFee => {
+                "Max fee is smaller than the minimal transaction cost (validation plus fee \
+                 transfer)"
+            }
+            GatewaySpecError::InvalidTransactionNonce => "Invalid transaction nonce",
+            GatewaySpecError::TxnHashNotFound => "Transaction hash not found",
+            GatewaySpecError::UnexpectedError => "An unexpected error occurred",
+            GatewaySpecError::UnsupportedContractClassVersion => {
+                "the contract class version is not supported"
+            }
+            GatewaySpecError::ValidationFailure => "Account validation failed",
+        }
+    }
+}
+
+fn mempool_spec_error(error: &MempoolError) -> (StatusCode, GatewaySpecError) {
+    match error {
+        MempoolError::DuplicateTransaction { .. } => {
+            (StatusCode::BAD_REQUEST, GatewaySpecError::DuplicateTx)
+        }
+        MempoolError::InsufficientBalance { .. } => {
+            (StatusCode::BAD_REQUEST, GatewaySpecError::InsufficientAccountBalance)
+        }
+        MempoolError::InsufficientFeeEscalation { .. } => {
+            (StatusCode::BAD_REQUEST, GatewaySpecError::InsufficientFeeEscalation)
+        }
+        MempoolError::MissingDeployAccount { .. } => {
+            (StatusCode::BAD_REQUEST, GatewaySpecError::ValidationFailure)
+        }
+        MempoolError::NonceTooFarAhead { .. } | MempoolError::NonceTooOld { .. } => {
+            (StatusCode::BAD_REQUEST, GatewaySpecError::InvalidTransactionNonce)
+        }
+        MempoolError::TooManyAccountTransactions { .. } => {
+            (StatusCode::TOO_MANY_REQUESTS, GatewaySpecError::ValidationFailure)
+        }
+        MempoolError::TransactionNotFound { .. } => {
+            (StatusCode::NOT_FOUND, GatewaySpecError::TxnHashNotFound)
+        }
+        MempoolError::MempoolFull { .. } => {
+            (StatusCode::SERVICE_UNAVAILABLE, GatewaySpecError::UnexpectedError)
+        }
+        MempoolError::FeltOutOfRange | MempoolError::StagedTransactionNotReturned { .. } => {
+            (StatusCode::INTERNAL_SERVER_ERROR, GatewaySpecError::UnexpectedError)
+        }
+    }
+}
+
+/// Details the mempool error by the identifiers and bounds it refers to.
+fn mempool_error_data(error: &MempoolError) -> Option<Value> {
+    match error {
+        MempoolError::DuplicateTransaction { tx_hash }
+        | MempoolError::InsufficientBalance { tx_hash }
+        | MempoolError::MempoolFull { tx_hash }
+        | MempoolError::MissingDeployAccount { tx_hash }
+        | MempoolError::StagedTransactionNotReturned { tx_hash }
+        | MempoolError::TransactionNotFound { tx_hash } => Some(json!({ "tx_hash": tx_hash })),
+        MempoolError::InsufficientFeeEscalation { tx_hash, existing_tx_hash } => {
+            Some(json!({ "tx_hash": tx_hash, "existing_tx_hash": existing_tx_hash }))
+        }
+        MempoolError::NonceTooFarAhead { tx_hash, tx_nonce, account_nonce }
+        | MempoolError::NonceTooOld { tx_hash, tx_nonce, account_nonce } => Some(json!({
+            "tx_hash": tx_hash,
+            "tx_nonce": tx_nonce,
+            "account_nonce": account_nonce,
+        })),
+        MempoolError::TooManyAccountTransactions { tx_hash, max_txs_per_account } => {
+            Some(json!({ "tx_hash": tx_hash, "max_txs_per_account": max_txs_per_account }))
+        }
+        MempoolError::FeltOutOfRange => None,
     }
 }
 
@@ -94,6 +339,71 @@ pub enum StatelessTransactionValidatorError {
 
 pub type StatelessTransactionValidatorResult<T> = Result<T, StatelessTransactionValidatorError>;
 
+impl StatelessTransactionValidatorError {
+    pub fn spec_error(&self) -> GatewaySpecError {
+        match self {
+            StatelessTransactionValidatorError::ZeroResourceBounds { .. } => {
+                GatewaySpecError::InsufficientMaxFee
+            }
+            StatelessTransactionValidatorError::CalldataTooLong { .. }
+            | StatelessTransactionValidatorError::SignatureTooLong { .. }
+            | StatelessTransactionValidatorError::EntryPointsNotUniquelySorted => {
+                GatewaySpecError::ValidationFailure
+            }
+            StatelessTransactionValidatorError::InvalidSierraVersion(_)
+            | StatelessTransactionValidatorError::UnsupportedSierraVersion { .. } => {
+                GatewaySpecError::UnsupportedContractClassVersion
+            }
+            StatelessTransactionValidatorError::BytecodeSizeTooLarge { .. }
+            | StatelessTransactionValidatorError::ContractClassObjectSizeTooLarge { .. } => {
+                GatewaySpecError::ContractClassSizeIsTooLarge
+            }
+        }
+    }
+
+    /// Details the error by the bounds it violates, if any, and by its description otherwise.
+    pub fn data(&self) -> Value {
+        match self {
+            StatelessTransactionValidatorError::ZeroResourceBounds {
+                resource,
+                resource_bounds,
+            } => {
+                json!({ "resource": resource, "resource_bounds": resource_bounds })
+            }
+            StatelessTransactionValidatorError::CalldataTooLong {
+                calldata_length,
+                max_calldata_length,
+            } => json!({
+                "calldata_length": calldata_length,
+                "max_calldata_length": max_calldata_length,
+            }),
+            StatelessTransactionValidatorError::SignatureTooLong {
+                signature_length,
+                max_signature_length,
+            } => json!({
+                "signature_length": signature_length,
+                "max_signature_length": max_signature_length,
+            }),
+            StatelessTransactionValidatorError::BytecodeSizeTooLarge {
+                bytecode_size,
+                max_bytecode_size,
+            } => json!({ "bytecode_size": bytecode_size, "max_bytecode_size": max_bytecode_size }),
+            StatelessTransactionValidatorError::ContractClassObjectSizeTooLarge {
+                contract_class_object_size,
+                max_contract_class_object_size,
+            } => json!({
+                "contract_class_object_size": contract_class_object_size,
+                "max_contract_class_object_size": max_contract_class_object_size,
+            }),
+            StatelessTransactionValidatorError::InvalidSierraVersion(_)
+            | StatelessTransactionValidatorError::UnsupportedSierraVersion { .. }
+            | StatelessTransactionValidatorError::EntryPointsNotUniquelySorted => {
+                Value::String(self.to_string())
+            }
+        }
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum StatefulTransactionValidatorError {
     #[error(transparent)]
@@ -108,6 +418,50 @@ pub enum StatefulTransactionValidatorError {
 
 pub type StatefulTransactionValidatorResult<T> = Result<T, StatefulTransactionValidatorError>;
 
+impl StatefulTransactionValidatorError {
+    /// Returns the spec error describing this error, along with the HTTP status code of its
+    /// response; failures to read the state are server errors.
+    pub fn spec_error(&self) -> (StatusCode, GatewaySpecError) {
+        let client_error = |spec_error| (StatusCode::BAD_REQUEST, spec_error);
+        let server_error = (StatusCode::INTERNAL_SERVER_ERROR, GatewaySpecError::UnexpectedError);
+
+        match self {
+            StatefulTransactionValidatorError::StarknetApiError(_)
+            | StatefulTransactionValidatorError::TransactionExecutionError(_) => {
+                client_error(GatewaySpecError::ValidationFailure)
+            }
+            StatefulTransactionValidatorError::StateError(_) => server_error,
+            StatefulTransactionValidatorError::StatefulValidatorError(error) => match error {
+                StatefulValidatorError::TransactionExecutionError(_) => {
+                    client_error(GatewaySpecError::ValidationFailure)
+                }
+                StatefulValidatorError::TransactionPreValidationError(error) => match error {
+                    TransactionPreValidationError::InvalidNonce { .. } => {
+                        client_error(GatewaySpecError::InvalidTransactionNonce)
+                    }
+                    TransactionPreValidationError::TransactionFeeError(error) => match error {
+                        TransactionFeeError::L1GasBoundsExceedBalance { .. }
+                        | TransactionFeeError::MaxFeeExceedsBalance { .. } => {
+                            client_error(GatewaySpecError::InsufficientAccountBalance)
+                        }
+                        TransactionFeeError::InsufficientL1Fee { .. }
+                        | TransactionFeeError::MaxFeeTooLow { .. }
+                        | TransactionFeeError::MaxL1GasAmountTooLow { .. }
+                        | TransactionFeeError::MaxL1GasPriceTooLow { .. }
+                        | TransactionFeeError::MissingL1GasBounds => {
+                            client_error(GatewaySpecError::InsufficientMaxFee)
+                        }
+                        _ => server_error,
+                    },
+                    TransactionPreValidationError::StateError(_) => server_error,
+                },
+                StatefulValidatorError::StateError(_)
+                | StatefulValidatorError::TransactionExecutorError(_) => server_error,
+            },
+        }
+    }
+}
+
 /// Errors originating from `[`Gateway::run`]` command, to be handled by infrastructure code.
 #[derive(Debug, Error)]
 pub enum GatewayRunError {
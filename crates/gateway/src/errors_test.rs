@@ -0,0 +1,157 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use axum::body::HttpBody;
+use axum::http::header::RETRY_AFTER;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use pretty_assertions::assert_eq;
+use rstest::rstest;
+use serde_json::{json, Value};
+use starknet_api::core::{CompiledClassHash, Nonce};
+use starknet_api::transaction::TransactionHash;
+use starknet_mempool_infra::component_client::definitions::ClientError;
+use starknet_mempool_types::communication::MempoolClientError;
+use starknet_mempool_types::errors::MempoolError;
+use starknet_types_core::felt::Felt;
+
+use crate::errors::{GatewayError, GatewaySpecError, StatelessTransactionValidatorError};
+use crate::rpc_objects::RpcSpecError;
+
+#[rstest]
+#[case::stateless_validation_failure(
+    StatelessTransactionValidatorError::CalldataTooLong {
+        calldata_length: 2,
+        max_calldata_length: 1
+    }.into(),
+    StatusCode::BAD_REQUEST,
+    GatewaySpecError::ValidationFailure,
+    Some(json!({ "calldata_length": 2, "max_calldata_length": 1 }))
+)]
+#[case::compiled_class_hash_mismatch(
+    GatewayError::CompiledClassHashMismatch {
+        supplied: CompiledClassHash(Felt::ONE),
+        hash_result: CompiledClassHash(Felt::TWO)
+    },
+    StatusCode::BAD_REQUEST,
+    GatewaySpecError::CompiledClassHashMismatch,
+    Some(json!({ "supplied": "0x1", "hash_result": "0x2" }))
+)]
+#[case::duplicate_tx(
+    MempoolClientError::MempoolError(MempoolError::DuplicateTransaction {
+        tx_hash: TransactionHash(Felt::ONE)
+    }).into(),
+    StatusCode::BAD_REQUEST,
+    GatewaySpecError::DuplicateTx,
+    Some(json!({ "tx_hash": "0x1" }))
+)]
+#[case::insufficient_fee_escalation(
+    MempoolClientError::MempoolError(MempoolError::InsufficientFeeEscalation {
+        tx_hash: TransactionHash(Felt::ONE),
+        existing_tx_hash: TransactionHash(Felt::TWO)
+    }).into(),
+    StatusCode::BAD_REQUEST,
+    GatewaySpecError::InsufficientFeeEscalation,
+    Some(json!({ "tx_hash": "0x1", "existing_tx_hash": "0x2" }))
+)]
+#[case::nonce_too_old(
+    MempoolClientError::MempoolError(MempoolError::NonceTooOld {
+        tx_hash: TransactionHash(Felt::ONE),
+        tx_nonce: Nonce(Felt::ONE),
+        account_nonce: Nonce(Felt::TWO)
+    }).into(),
+    StatusCode::BAD_REQUEST,
+    GatewaySpecError::InvalidTransactionNonce,
+    Some(json!({ "tx_hash": "0x1", "tx_nonce": "0x1", "account_nonce": "0x2" }))
+)]
+#[case::tx_not_found(
+    MempoolClientError::MempoolError(MempoolError::TransactionNotFound {
+        tx_hash: TransactionHash(Felt::ONE)
+    }).into(),
+    StatusCode::NOT_FOUND,
+    GatewaySpecError::TxnHashNotFound,
+    Some(json!({ "tx_hash": "0x1" }))
+)]
+#[case::mempool_unreachable(
+    MempoolClientError::ClientError(ClientError::UnexpectedResponse).into(),
+    StatusCode::INTERNAL_SERVER_ERROR,
+    GatewaySpecError::UnexpectedError,
+    None
+)]
+#[case::mempool_full(
+    MempoolClientError::MempoolError(MempoolError::MempoolFull {
+        tx_hash: TransactionHash(Felt::ONE)
+    }).into(),
+    StatusCode::SERVICE_UNAVAILABLE,
+    GatewaySpecError::UnexpectedError,
+    None
+)]
+#[case::rate_limited(
+    GatewayError::RateLimited { retry_after: Duration::from_secs(1) },
+    StatusCode::TOO_MANY_REQUESTS,
+    GatewaySpecError::UnexpectedError,
+    Some(json!({ "retry_after": 1 }))
+)]
+#[case::overloaded(
+    GatewayError::Overloaded { retry_after: Duration::from_millis(1500) },
+    StatusCode::SERVICE_UNAVAILABLE,
+    GatewaySpecError::UnexpectedError,
+    Some(json!({ "retry_after": 2 }))
+)]
+#[tokio::test]
+async fn test_error_response(
+    #[case] error: GatewayError,
+    #[case] expected_status_code: StatusCode,
+    #[case] expected_spec_error: GatewaySpecError,
+    #[case] expected_data: Option<Value>,
+) {
+    let response = error.into_response();
+
+    assert_eq!(response.status(), expected_status_code);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let expected_body = RpcSpecError {
+        code: expected_spec_error.code(),
+        message: expected_spec_error.message().to_string(),
+        data: expected_data,
+    };
+    assert_eq!(serde_json::from_slice::<RpcSpecError>(&body).unwrap(), expected_body);
+}
+
+#[test]
+fn test_spec_error_codes_are_distinct() {
+    let spec_errors = [
+        GatewaySpecError::CompilationFailed,
+        GatewaySpecError::CompiledClassHashMismatch,
+        GatewaySpecError::ContractClassSizeIsTooLarge,
+        GatewaySpecError::DuplicateTx,
+        GatewaySpecError::InsufficientAccountBalance,
+        GatewaySpecError::InsufficientFeeEscalation,
+        GatewaySpecError::InsufficientMaxFee,
+        GatewaySpecError::InvalidTransactionNonce,
+        GatewaySpecError::TxnHashNotFound,
+        GatewaySpecError::UnexpectedError,
+        GatewaySpecError::UnsupportedContractClassVersion,
+        GatewaySpecError::ValidationFailure,
+    ];
+
+    let codes: HashSet<u16> = spec_errors.iter().map(GatewaySpecError::code).collect();
+    assert_eq!(codes.len(), spec_errors.len());
+    assert_eq!(GatewaySpecError::InsufficientFeeEscalation.code(), 1000);
+}
+
+#[rstest]
+#[case::whole_seconds(Duration::from_secs(2), "2")]
+#[case::rounded_up(Duration::from_millis(250), "1")]
+fn test_retry_after_header(#[case] retry_after: Duration, #[case] expected_header: &str) {
+    let response = GatewayError::RateLimited { retry_after }.into_response();
+    assert_eq!(response.headers()[RETRY_AFTER], expected_header);
+}
+
+#[test]
+fn test_no_retry_after_header_for_permanent_errors() {
+    let error = GatewayError::CompiledClassHashMismatch {
+        supplied: CompiledClassHash::default(),
+        hash_result: CompiledClassHash::default(),
+    };
+    assert!(error.into_response().headers().get(RETRY_AFTER).is_none());
+}
@@ -1,31 +1,48 @@
 use std::clone::Clone;
-use std::net::SocketAddr;
+use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use axum::extract::State;
+use axum::body::Bytes;
+use axum::extract::{ConnectInfo, Path, State};
+use axum::http::header::{CONTENT_TYPE, RETRY_AFTER};
+use axum::http::{HeaderMap, HeaderName, StatusCode};
+use axum::response::{IntoResponse, Response};
 use axum::routing::{get, post};
 use axum::{Json, Router};
-use starknet_api::rpc_transaction::RPCTransaction;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use starknet_api::core::{ClassHash, ContractAddress};
+use starknet_api::rpc_transaction::{RPCDeclareTransaction, RPCTransaction};
 use starknet_api::transaction::TransactionHash;
 use starknet_mempool_infra::component_runner::{ComponentStartError, ComponentStarter};
 use starknet_mempool_types::communication::SharedMempoolClient;
-use starknet_mempool_types::mempool_types::{Account, MempoolInput};
+use starknet_mempool_types::mempool_types::{Account, MempoolInput, TransactionStatus};
 use tracing::{info, instrument};
 
-use crate::compilation::GatewayCompiler;
+use crate::admission_control::AdmissionControl;
+use crate::compilation::{to_class_info, GatewayCompiler};
 use crate::config::{GatewayConfig, GatewayNetworkConfig, RpcStateReaderConfig};
-use crate::errors::{GatewayError, GatewayResult, GatewayRunError};
+use crate::errors::{retry_after_header_value, GatewayResult, GatewayRunError};
+use crate::health::{HealthReport, DEPENDENCY_CHECK_TIMEOUT};
+use crate::json_rpc::{
+    AddTransactionMethod, JsonRpcError, JsonRpcRequest, JsonRpcResponse, JSON_RPC_VERSION,
+};
 use crate::rpc_state_reader::RpcStateReaderFactory;
 use crate::state_reader::StateReaderFactory;
-use crate::stateful_transaction_validator::StatefulTransactionValidator;
+use crate::stateful_transaction_validator::{
+    skip_stateful_validations, StatefulTransactionValidator,
+};
 use crate::stateless_transaction_validator::StatelessTransactionValidator;
-use crate::utils::{external_tx_to_thin_tx, get_sender_address};
+use crate::utils::{compute_class_hash, external_tx_to_thin_tx, get_sender_address};
 
 #[cfg(test)]
 #[path = "gateway_test.rs"]
 pub mod gateway_test;
 
+/// The content type of the Prometheus text exposition format, in which metrics are served.
+pub const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
 pub struct Gateway {
     pub config: GatewayConfig,
     app_state: AppState,
@@ -38,6 +55,7 @@ pub struct AppState {
     pub state_reader_factory: Arc<dyn StateReaderFactory>,
     pub gateway_compiler: GatewayCompiler,
     pub mempool_client: SharedMempoolClient,
+    pub admission_control: Arc<AdmissionControl>,
 }
 
 impl Gateway {
@@ -57,6 +75,9 @@ impl Gateway {
             state_reader_factory,
             gateway_compiler,
             mempool_client,
+            admission_control: Arc::new(AdmissionControl::new(
+                config.admission_control_config.clone(),
+            )),
         };
         Gateway { config, app_state }
     }
@@ -68,30 +89,164 @@ impl Gateway {
         let app = self.app();
 
         // Create a server that runs forever.
-        Ok(axum::Server::bind(&addr).serve(app.into_make_service()).await?)
+        // Client addresses are provided for rate limiting.
+        Ok(axum::Server::bind(&addr)
+            .serve(app.into_make_service_with_connect_info::<SocketAddr>())
+            .await?)
     }
 
     pub fn app(&self) -> Router {
         Router::new()
             .route("/is_alive", get(is_alive))
+            .route("/is_ready", get(is_ready))
             .route("/add_tx", post(add_tx))
+            .route("/rpc", post(json_rpc))
+            .route("/tx_status/:tx_hash", get(get_tx_status))
+            .route("/metrics", get(get_metrics))
             .with_state(self.app_state.clone())
     }
 }
 
+/// The response to a transaction added through the gateway, shaped as the results of the
+/// Starknet spec's write API.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum AddTransactionResponse {
+    Declare { transaction_hash: TransactionHash, class_hash: ClassHash },
+    DeployAccount { transaction_hash: TransactionHash, contract_address: ContractAddress },
+    // Must be last: deserialization picks the first variant whose fields are all present.
+    Invoke { transaction_hash: TransactionHash },
+}
+
+impl AddTransactionResponse {
+    pub fn tx_hash(&self) -> TransactionHash {
+        match self {
+            AddTransactionResponse::Declare { transaction_hash, .. }
+            | AddTransactionResponse::DeployAccount { transaction_hash, .. }
+            | AddTransactionResponse::Invoke { transaction_hash } => *transaction_hash,
+        }
+    }
+}
+
 // Gateway handlers.
 
+/// Liveness probe: succeeds as long as the gateway serves requests.
 #[instrument]
 async fn is_alive() -> GatewayResult<String> {
-    unimplemented!("Future handling should be implemented here.");
+    Ok("Gateway is alive".to_string())
+}
+
+/// Readiness probe: reports the health of the gateway's dependencies, and succeeds only if all
+/// of them are healthy.
+#[instrument(skip(app_state))]
+async fn is_ready(State(app_state): State<AppState>) -> (StatusCode, Json<HealthReport>) {
+    let report = HealthReport::check(
+        &app_state.mempool_client,
+        app_state.state_reader_factory,
+        DEPENDENCY_CHECK_TIMEOUT,
+    )
+    .await;
+    let status_code =
+        if report.is_ready() { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status_code, Json(report))
 }
 
 #[instrument(skip(app_state))]
 async fn add_tx(
     State(app_state): State<AppState>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
     Json(tx): Json<RPCTransaction>,
-) -> GatewayResult<Json<TransactionHash>> {
-    let mempool_input = tokio::task::spawn_blocking(move || {
+) -> GatewayResult<Json<AddTransactionResponse>> {
+    let client_ip = app_state.admission_control.client_ip(peer_ip(connect_info), &headers);
+    Ok(Json(submit_tx(app_state, client_ip, tx).await?))
+}
+
+/// Serves the write methods of the Starknet spec's JSON-RPC API. Errors are reported in the
+/// JSON-RPC response, which is sent with an OK status; responses to rejections due to rate limits
+/// or overload are sent with the status and `Retry-After` header of the REST API instead, so that
+/// clients back off.
+#[instrument(skip(app_state, body))]
+async fn json_rpc(
+    State(app_state): State<AppState>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let request: Value = match serde_json::from_slice(&body) {
+        Ok(request) => request,
+        Err(error) => {
+            return Json(JsonRpcResponse::new(Value::Null, Err(JsonRpcError::parse_error(error))))
+                .into_response();
+        }
+    };
+
+    let id = request.get("id").cloned().unwrap_or_default();
+    let tx = match serde_json::from_value(request)
+        .map_err(JsonRpcError::invalid_request)
+        .and_then(parse_json_rpc_request)
+    {
+        Ok(tx) => tx,
+        Err(error) => return Json(JsonRpcResponse::new(id, Err(error))).into_response(),
+    };
+
+    let client_ip = app_state.admission_control.client_ip(peer_ip(connect_info), &headers);
+    match submit_tx(app_state, client_ip, tx).await {
+        Ok(response) => {
+            let result = serde_json::to_value(response)
+                .expect("Transaction responses should be serializable.");
+            Json(JsonRpcResponse::new(id, Ok(result))).into_response()
+        }
+        Err(error) => {
+            let retry_after = error.retry_after();
+            let (status_code, _) = error.spec_error();
+            let body = Json(JsonRpcResponse::new(id, Err(error.into())));
+            match retry_after {
+                Some(retry_after) => {
+                    (status_code, [(RETRY_AFTER, retry_after_header_value(retry_after))], body)
+                        .into_response()
+                }
+                None => body.into_response(),
+            }
+        }
+    }
+}
+
+/// Returns the transaction added by the JSON-RPC request.
+fn parse_json_rpc_request(request: JsonRpcRequest) -> Result<RPCTransaction, JsonRpcError> {
+    if request.jsonrpc != JSON_RPC_VERSION {
+        return Err(JsonRpcError::invalid_request(format!(
+            "Unsupported JSON-RPC version: {}.",
+            request.jsonrpc
+        )));
+    }
+    let method = AddTransactionMethod::from_name(&request.method)
+        .ok_or_else(|| JsonRpcError::method_not_found(&request.method))?;
+    method.parse_params(request.params)
+}
+
+/// Returns the IP of the connected peer; unknown if the gateway is served without connection
+/// info.
+fn peer_ip(connect_info: Option<ConnectInfo<SocketAddr>>) -> Option<IpAddr> {
+    connect_info.map(|ConnectInfo(peer_address)| peer_address.ip())
+}
+
+/// Processes the transaction and adds it to the mempool, once admitted by the admission control.
+/// Clients whose IP is unknown are not rate limited by IP.
+async fn submit_tx(
+    app_state: AppState,
+    client_ip: Option<IpAddr>,
+    tx: RPCTransaction,
+) -> GatewayResult<AddTransactionResponse> {
+    let admission_control = &app_state.admission_control;
+    if let Some(client_ip) = client_ip {
+        admission_control.check_client_ip(client_ip)?;
+    }
+    let validation_permit = admission_control.acquire_validation_permit().await?;
+
+    let (mempool_input, response) = tokio::task::spawn_blocking(move || {
+        // Held throughout the validation, even if the request is dropped meanwhile.
+        let _validation_permit = validation_permit;
         process_tx(
             app_state.stateless_tx_validator,
             app_state.stateful_tx_validator.as_ref(),
@@ -102,15 +257,28 @@ async fn add_tx(
     })
     .await??;
 
-    let tx_hash = mempool_input.tx.tx_hash;
+    // Charged only once the transaction is validated, so that invalid transactions, which anyone
+    // may send on behalf of a sender, don't exhaust its limit.
+    app_state.admission_control.check_sender_address(mempool_input.account.sender_address)?;
+    app_state.mempool_client.add_tx(mempool_input).await?;
+    Ok(response)
+}
 
-    app_state
-        .mempool_client
-        .add_tx(mempool_input)
-        .await
-        .map_err(|e| GatewayError::MessageSendError(e.to_string()))?;
-    // TODO: Also return `ContractAddress` for deploy and `ClassHash` for Declare.
-    Ok(Json(tx_hash))
+#[instrument(skip(app_state))]
+async fn get_tx_status(
+    State(app_state): State<AppState>,
+    Path(tx_hash): Path<TransactionHash>,
+) -> GatewayResult<Json<TransactionStatus>> {
+    let tx_status = app_state.mempool_client.get_tx_status(tx_hash).await?;
+    Ok(Json(tx_status))
+}
+
+#[instrument(skip(app_state))]
+async fn get_metrics(
+    State(app_state): State<AppState>,
+) -> GatewayResult<([(HeaderName, &'static str); 1], String)> {
+    let metrics = app_state.mempool_client.get_metrics().await?;
+    Ok(([(CONTENT_TYPE, PROMETHEUS_CONTENT_TYPE)], metrics))
 }
 
 fn process_tx(
@@ -119,28 +287,49 @@ fn process_tx(
     state_reader_factory: &dyn StateReaderFactory,
     gateway_compiler: GatewayCompiler,
     tx: RPCTransaction,
-) -> GatewayResult<MempoolInput> {
-    // TODO(Arni, 1/5/2024): Perform congestion control.
-
+) -> GatewayResult<(MempoolInput, AddTransactionResponse)> {
     // Perform stateless validations.
     stateless_tx_validator.validate(&tx)?;
 
     // Compile Sierra to Casm.
-    let optional_class_info = match &tx {
+    let optional_compiled_class_info = match &tx {
         RPCTransaction::Declare(declare_tx) => {
-            Some(gateway_compiler.process_declare_tx(declare_tx)?)
+            Some(gateway_compiler.compile_declare_tx(declare_tx)?)
         }
         _ => None,
     };
+    let optional_class_info =
+        optional_compiled_class_info.as_ref().map(to_class_info).transpose()?;
 
     let validator = stateful_tx_validator.instantiate_validator(state_reader_factory)?;
     let tx_hash = stateful_tx_validator.run_validate(&tx, optional_class_info, validator)?;
 
-    // TODO(Arni): Add the Sierra and the Casm to the mempool input.
-    Ok(MempoolInput {
-        tx: external_tx_to_thin_tx(&tx, tx_hash),
-        account: Account { sender_address: get_sender_address(&tx), ..Default::default() },
-    })
+    let sender_address = get_sender_address(&tx)?;
+    let account_state =
+        stateful_tx_validator.get_account_state(state_reader_factory, sender_address)?;
+    let depends_on_deploy_account = skip_stateful_validations(&tx, account_state.nonce);
+
+    let response = match &tx {
+        RPCTransaction::Declare(RPCDeclareTransaction::V3(declare_tx)) => {
+            AddTransactionResponse::Declare {
+                transaction_hash: tx_hash,
+                class_hash: compute_class_hash(&declare_tx.contract_class)?,
+            }
+        }
+        // The sender of a deploy account transaction is the deployed account.
+        RPCTransaction::DeployAccount(_) => AddTransactionResponse::DeployAccount {
+            transaction_hash: tx_hash,
+            contract_address: sender_address,
+        },
+        RPCTransaction::Invoke(_) => AddTransactionResponse::Invoke { transaction_hash: tx_hash },
+    };
+
+    let mempool_input = MempoolInput {
+        tx: external_tx_to_thin_tx(tx, tx_hash, optional_compiled_class_info)?,
+        account: Account { sender_address, state: account_state },
+        depends_on_deploy_account,
+    };
+    Ok((mempool_input, response))
 }
 
 pub fn create_gateway(
@@ -1,24 +1,54 @@
-use std::sync::Arc;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
+use assert_matches::assert_matches;
 use axum::body::{Bytes, HttpBody};
-use axum::extract::State;
-use axum::http::StatusCode;
+use axum::extract::{ConnectInfo, Path, State};
+use axum::http::header::{CONTENT_TYPE, RETRY_AFTER};
+use axum::http::{HeaderMap, StatusCode};
 use axum::response::{IntoResponse, Response};
 use blockifier::context::ChainInfo;
-use blockifier::test_utils::CairoVersion;
-use mempool_test_utils::starknet_api_test_utils::invoke_tx;
+use blockifier::test_utils::initial_test_state::fund_account;
+use blockifier::test_utils::{CairoVersion, BALANCE};
+use mempool_test_utils::invoke_tx_args;
+use mempool_test_utils::starknet_api_test_utils::{
+    deploy_account_tx, deployed_account_contract_address, executable_resource_bounds_mapping,
+    external_invoke_tx, invoke_tx,
+};
 use mockall::predicate::eq;
-use starknet_api::core::ContractAddress;
+use rstest::rstest;
+use serde_json::{json, Value};
+use starknet_api::core::{ClassHash, ContractAddress, Nonce, PatriciaKey};
 use starknet_api::rpc_transaction::RPCTransaction;
-use starknet_api::transaction::TransactionHash;
-use starknet_mempool_types::communication::MockMempoolClient;
-use starknet_mempool_types::mempool_types::{Account, AccountState, MempoolInput, ThinTransaction};
+use starknet_api::transaction::{Calldata, TransactionHash};
+use starknet_api::{class_hash, contract_address, felt, patricia_key};
+use starknet_mempool::mempool::Mempool;
+use starknet_mempool_infra::component_client::definitions::ClientError;
+use starknet_mempool_types::communication::{
+    MempoolClientError, MempoolClientResult, MockMempoolClient,
+};
+use starknet_mempool_types::mempool_types::{
+    Account, AccountState, ExecutableTransaction, MempoolInput, ThinTransaction, TransactionStatus,
+};
+use starknet_types_core::felt::Felt;
 
+use crate::admission_control::AdmissionControl;
 use crate::compilation::GatewayCompiler;
 use crate::config::{
-    GatewayCompilerConfig, StatefulTransactionValidatorConfig, StatelessTransactionValidatorConfig,
+    AdmissionControlConfig, GatewayCompilerConfig, RateLimitConfig,
+    StatefulTransactionValidatorConfig, StatelessTransactionValidatorConfig,
+};
+use crate::errors::GatewaySpecError;
+use crate::gateway::{
+    add_tx, get_metrics, get_tx_status, is_alive, is_ready, json_rpc, AddTransactionResponse,
+    AppState, SharedMempoolClient, PROMETHEUS_CONTENT_TYPE,
 };
-use crate::gateway::{add_tx, AppState, SharedMempoolClient};
+use crate::health::{DependencyHealth, HealthReport};
+use crate::json_rpc::{
+    JsonRpcResponse, INVALID_PARAMS, INVALID_REQUEST, METHOD_NOT_FOUND, PARSE_ERROR,
+};
+use crate::state_reader::{MockStateReaderFactory, StateReaderFactory};
 use crate::state_reader_test_utils::{local_test_state_reader_factory, TestStateReaderFactory};
 use crate::stateful_transaction_validator::StatefulTransactionValidator;
 use crate::stateless_transaction_validator::StatelessTransactionValidator;
@@ -45,11 +75,16 @@ pub fn app_state(
         gateway_compiler: GatewayCompiler { config: GatewayCompilerConfig {} },
         state_reader_factory: Arc::new(state_reader_factory),
         mempool_client,
+        admission_control: Arc::new(AdmissionControl::new(AdmissionControlConfig::default())),
     }
 }
 
 type SenderAddress = ContractAddress;
 
+fn client_address() -> SocketAddr {
+    SocketAddr::from(([127, 0, 0, 1], 8080))
+}
+
 fn create_tx() -> (RPCTransaction, SenderAddress) {
     let tx = invoke_tx(CairoVersion::Cairo1);
     let sender_address = match &tx {
@@ -61,36 +96,402 @@ fn create_tx() -> (RPCTransaction, SenderAddress) {
     (tx, sender_address)
 }
 
+fn expect_add_tx(tx: &RPCTransaction, sender_address: SenderAddress) -> MockMempoolClient {
+    let tx_hash = calculate_hash(tx);
+    let mut mock_mempool_client = MockMempoolClient::new();
+    mock_mempool_client
+        .expect_add_tx()
+        .once()
+        .with(eq(MempoolInput {
+            tx: ThinTransaction {
+                sender_address,
+                tx_hash,
+                tip: *tx.tip(),
+                nonce: *tx.nonce(),
+                resource_bounds: tx.resource_bounds().clone(),
+                executable_tx: Arc::new(ExecutableTransaction {
+                    tx: tx.clone(),
+                    compiled_class_info: None,
+                }),
+            },
+            account: Account {
+                sender_address,
+                state: AccountState { nonce: *tx.nonce(), balance: BALANCE },
+            },
+            depends_on_deploy_account: false,
+        }))
+        .return_once(|_| Ok(()));
+    mock_mempool_client
+}
+
 #[tokio::test]
 async fn test_add_tx() {
     let (tx, sender_address) = create_tx();
     let tx_hash = calculate_hash(&tx);
 
+    let mock_mempool_client = expect_add_tx(&tx, sender_address);
+    let state_reader_factory = local_test_state_reader_factory(CairoVersion::Cairo1, false);
+    let app_state = app_state(Arc::new(mock_mempool_client), state_reader_factory);
+
+    let response =
+        add_tx(State(app_state), Some(ConnectInfo(client_address())), HeaderMap::new(), tx.into())
+            .await
+            .into_response();
+
+    let status_code = response.status();
+    let response_bytes = &to_bytes(response).await;
+
+    assert_eq!(status_code, StatusCode::OK, "{response_bytes:?}");
+    assert_eq!(
+        AddTransactionResponse::Invoke { transaction_hash: tx_hash },
+        serde_json::from_slice(response_bytes).unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_add_deploy_account_and_invoke_txs() {
+    let deploy_account_tx = deploy_account_tx();
+    let deployed_address = deployed_account_contract_address(&deploy_account_tx);
+    // An invoke transaction sent along with the deploy account transaction, before the account is
+    // deployed.
+    let invoke_tx = external_invoke_tx(invoke_tx_args!(
+        sender_address: deployed_address,
+        nonce: Nonce(Felt::ONE),
+        resource_bounds: executable_resource_bounds_mapping(),
+    ));
+
+    // The deployed account is funded in advance.
+    let mut state_reader_factory = local_test_state_reader_factory(CairoVersion::Cairo1, false);
+    fund_account(
+        &ChainInfo::create_for_testing(),
+        deployed_address,
+        BALANCE,
+        &mut state_reader_factory.state_reader.blockifier_state_reader,
+    );
+
+    // The transactions are added to an actual mempool, which accepts the invoke transaction only
+    // if it is sent by the pending deploy account transaction's account.
+    let mempool = Arc::new(Mutex::new(Mempool::empty()));
+    let mut mock_mempool_client = MockMempoolClient::new();
+    let mempool_clone = mempool.clone();
+    mock_mempool_client.expect_add_tx().times(2).returning(move |input| {
+        mempool_clone.lock().unwrap().add_tx(input).map_err(MempoolClientError::MempoolError)
+    });
+    let app_state = app_state(Arc::new(mock_mempool_client), state_reader_factory);
+
+    let mut tx_hashes = Vec::new();
+    for tx in [deploy_account_tx, invoke_tx] {
+        let response = add_tx(
+            State(app_state.clone()),
+            Some(ConnectInfo(client_address())),
+            HeaderMap::new(),
+            tx.into(),
+        )
+        .await
+        .into_response();
+        let status_code = response.status();
+        let response_bytes = &to_bytes(response).await;
+        assert_eq!(status_code, StatusCode::OK, "{response_bytes:?}");
+        let response: AddTransactionResponse = serde_json::from_slice(response_bytes).unwrap();
+        tx_hashes.push(response.tx_hash());
+    }
+
+    let txs = mempool.lock().unwrap().get_txs(2).unwrap();
+    assert_eq!(txs.iter().map(|tx| tx.tx_hash).collect::<Vec<_>>(), tx_hashes);
+    assert!(txs.iter().all(|tx| tx.sender_address == deployed_address));
+}
+
+#[tokio::test]
+async fn test_add_tx_without_connect_info() {
+    let (tx, sender_address) = create_tx();
+
+    let mock_mempool_client = expect_add_tx(&tx, sender_address);
+    let state_reader_factory = local_test_state_reader_factory(CairoVersion::Cairo1, false);
+    let mut app_state = app_state(Arc::new(mock_mempool_client), state_reader_factory);
+    app_state.admission_control = Arc::new(AdmissionControl::new(AdmissionControlConfig {
+        client_ip_rate_limit: Some(RateLimitConfig::default()),
+        ..Default::default()
+    }));
+
+    let response =
+        add_tx(State(app_state), None, HeaderMap::new(), tx.into()).await.into_response();
+
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_invalid_tx_is_not_charged_to_sender() {
+    let (_, sender_address) = create_tx();
+    // Exceeds the maximal calldata length.
+    let tx = external_invoke_tx(invoke_tx_args!(
+        sender_address,
+        calldata: Calldata(Arc::new(vec![Felt::ZERO; 11])),
+        resource_bounds: executable_resource_bounds_mapping(),
+    ));
+
+    let state_reader_factory = local_test_state_reader_factory(CairoVersion::Cairo1, false);
+    let mut app_state = app_state(Arc::new(MockMempoolClient::new()), state_reader_factory);
+    app_state.admission_control = Arc::new(AdmissionControl::new(AdmissionControlConfig {
+        sender_address_rate_limit: Some(RateLimitConfig { burst_size: 1, requests_per_second: 1 }),
+        ..Default::default()
+    }));
+
+    let response = add_tx(
+        State(app_state.clone()),
+        Some(ConnectInfo(client_address())),
+        HeaderMap::new(),
+        tx.into(),
+    )
+    .await
+    .into_response();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_matches!(app_state.admission_control.check_sender_address(sender_address), Ok(()));
+}
+
+#[rstest]
+#[case::declare(AddTransactionResponse::Declare {
+    transaction_hash: TransactionHash(felt!("0x1")),
+    class_hash: class_hash!("0x2"),
+})]
+#[case::deploy_account(AddTransactionResponse::DeployAccount {
+    transaction_hash: TransactionHash(felt!("0x1")),
+    contract_address: contract_address!("0x2"),
+})]
+#[case::invoke(AddTransactionResponse::Invoke { transaction_hash: TransactionHash(felt!("0x1")) })]
+fn test_add_tx_response_serde(#[case] response: AddTransactionResponse) {
+    let serialized = serde_json::to_string(&response).unwrap();
+    assert_eq!(serde_json::from_str::<AddTransactionResponse>(&serialized).unwrap(), response);
+}
+
+#[tokio::test]
+async fn test_json_rpc_add_invoke_tx() {
+    let (tx, sender_address) = create_tx();
+    let tx_hash = calculate_hash(&tx);
+
+    let mock_mempool_client = expect_add_tx(&tx, sender_address);
+    let state_reader_factory = local_test_state_reader_factory(CairoVersion::Cairo1, false);
+    let app_state = app_state(Arc::new(mock_mempool_client), state_reader_factory);
+
+    let request = json!({
+        "jsonrpc": "2.0",
+        "method": "starknet_addInvokeTransaction",
+        "params": {"invoke_transaction": tx},
+        "id": 1,
+    });
+    let response = json_rpc(
+        State(app_state),
+        Some(ConnectInfo(client_address())),
+        HeaderMap::new(),
+        request.to_string().into(),
+    )
+    .await;
+    let response = json_rpc_response(response).await;
+
+    assert_eq!(
+        response,
+        JsonRpcResponse {
+            jsonrpc: "2.0".to_string(),
+            result: Some(json!({"transaction_hash": tx_hash})),
+            error: None,
+            id: json!(1),
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_json_rpc_rate_limited() {
+    let (tx, _) = create_tx();
+    let state_reader_factory = local_test_state_reader_factory(CairoVersion::Cairo1, false);
+    let mut app_state = app_state(Arc::new(MockMempoolClient::new()), state_reader_factory);
+    app_state.admission_control = Arc::new(AdmissionControl::new(AdmissionControlConfig {
+        client_ip_rate_limit: Some(RateLimitConfig { burst_size: 1, requests_per_second: 1 }),
+        ..Default::default()
+    }));
+    // Exhausts the client's limit.
+    app_state.admission_control.check_client_ip(client_address().ip()).unwrap();
+
+    let request = json!({
+        "jsonrpc": "2.0",
+        "method": "starknet_addInvokeTransaction",
+        "params": {"invoke_transaction": tx},
+        "id": 1,
+    });
+    let response = json_rpc(
+        State(app_state),
+        Some(ConnectInfo(client_address())),
+        HeaderMap::new(),
+        request.to_string().into(),
+    )
+    .await;
+
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(response.headers()[RETRY_AFTER], "1");
+    let response: JsonRpcResponse = serde_json::from_slice(&to_bytes(response).await).unwrap();
+    let error = response.error.unwrap();
+    assert_eq!(error.code, i32::from(GatewaySpecError::UnexpectedError.code()));
+    assert_eq!(error.data, Some(json!({ "retry_after": 1 })));
+}
+
+#[rstest]
+#[case::parse_error("{", PARSE_ERROR, Value::Null)]
+#[case::invalid_request(r#"{"jsonrpc": "2.0", "id": 1}"#, INVALID_REQUEST, json!(1))]
+#[case::unsupported_version(
+    r#"{"jsonrpc": "1.0", "method": "starknet_addInvokeTransaction", "id": 1}"#,
+    INVALID_REQUEST,
+    json!(1)
+)]
+#[case::method_not_found(
+    r#"{"jsonrpc": "2.0", "method": "starknet_getNonce", "params": [], "id": "a"}"#,
+    METHOD_NOT_FOUND,
+    json!("a")
+)]
+#[case::invalid_params(
+    r#"{"jsonrpc": "2.0", "method": "starknet_addInvokeTransaction", "params": [], "id": 2}"#,
+    INVALID_PARAMS,
+    json!(2)
+)]
+#[tokio::test]
+async fn test_json_rpc_invalid_request(
+    #[case] request: &'static str,
+    #[case] expected_code: i32,
+    #[case] expected_id: Value,
+) {
+    let state_reader_factory = local_test_state_reader_factory(CairoVersion::Cairo1, false);
+    let app_state = app_state(Arc::new(MockMempoolClient::new()), state_reader_factory);
+
+    let response = json_rpc(
+        State(app_state),
+        Some(ConnectInfo(client_address())),
+        HeaderMap::new(),
+        Bytes::from(request),
+    )
+    .await;
+    let response = json_rpc_response(response).await;
+
+    assert_eq!(response.result, None);
+    assert_eq!(response.error.unwrap().code, expected_code);
+    assert_eq!(response.id, expected_id);
+}
+
+#[tokio::test]
+async fn test_get_tx_status() {
+    let (tx, _) = create_tx();
+    let tx_hash = calculate_hash(&tx);
+
     let mut mock_mempool_client = MockMempoolClient::new();
     mock_mempool_client
-        .expect_add_tx()
+        .expect_get_tx_status()
         .once()
-        .with(eq(MempoolInput {
-            tx: ThinTransaction { sender_address, tx_hash, tip: *tx.tip(), nonce: *tx.nonce() },
-            account: Account { sender_address, state: AccountState { nonce: *tx.nonce() } },
-        }))
-        .return_once(|_| Ok(()));
+        .with(eq(tx_hash))
+        .return_once(|_| Ok(TransactionStatus::Queued));
     let state_reader_factory = local_test_state_reader_factory(CairoVersion::Cairo1, false);
     let app_state = app_state(Arc::new(mock_mempool_client), state_reader_factory);
 
-    let response = add_tx(State(app_state), tx.into()).await.into_response();
+    let response = get_tx_status(State(app_state), Path(tx_hash)).await.into_response();
 
     let status_code = response.status();
     let response_bytes = &to_bytes(response).await;
 
     assert_eq!(status_code, StatusCode::OK, "{response_bytes:?}");
-    assert_eq!(tx_hash, serde_json::from_slice(response_bytes).unwrap());
+    assert_eq!(
+        TransactionStatus::Queued,
+        serde_json::from_slice::<TransactionStatus>(response_bytes).unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_get_metrics() {
+    let metrics = "mempool_pool_size 1\n";
+
+    let mut mock_mempool_client = MockMempoolClient::new();
+    mock_mempool_client.expect_get_metrics().once().return_once(|| Ok(metrics.to_string()));
+    let state_reader_factory = local_test_state_reader_factory(CairoVersion::Cairo1, false);
+    let app_state = app_state(Arc::new(mock_mempool_client), state_reader_factory);
+
+    let response = get_metrics(State(app_state)).await.into_response();
+
+    let status_code = response.status();
+    let content_type = response.headers()[CONTENT_TYPE].clone();
+    let response_bytes = &to_bytes(response).await;
+
+    assert_eq!(status_code, StatusCode::OK, "{response_bytes:?}");
+    assert_eq!(content_type, PROMETHEUS_CONTENT_TYPE);
+    assert_eq!(response_bytes, metrics.as_bytes());
+}
+
+#[tokio::test]
+async fn test_is_alive() {
+    let response = is_alive().await.into_response();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[rstest]
+#[case::ready(Ok(()), StatusCode::OK, DependencyHealth::Healthy)]
+#[case::mempool_unreachable(
+    Err(MempoolClientError::ClientError(ClientError::UnexpectedResponse)),
+    StatusCode::SERVICE_UNAVAILABLE,
+    DependencyHealth::Unhealthy { error: ClientError::UnexpectedResponse.to_string() }
+)]
+#[tokio::test]
+async fn test_is_ready(
+    #[case] mempool_result: MempoolClientResult<()>,
+    #[case] expected_status_code: StatusCode,
+    #[case] expected_mempool_health: DependencyHealth,
+) {
+    let mut mock_mempool_client = MockMempoolClient::new();
+    mock_mempool_client.expect_is_alive().once().return_once(|| mempool_result);
+    let state_reader_factory = local_test_state_reader_factory(CairoVersion::Cairo1, false);
+    let app_state = app_state(Arc::new(mock_mempool_client), state_reader_factory);
+
+    let response = is_ready(State(app_state)).await.into_response();
+
+    let status_code = response.status();
+    let response_bytes = &to_bytes(response).await;
+
+    assert_eq!(status_code, expected_status_code, "{response_bytes:?}");
+    assert_eq!(
+        serde_json::from_slice::<HealthReport>(response_bytes).unwrap(),
+        HealthReport { mempool: expected_mempool_health, state_reader: DependencyHealth::Healthy }
+    );
+}
+
+#[tokio::test]
+async fn test_health_check_times_out() {
+    let mut mock_mempool_client = MockMempoolClient::new();
+    mock_mempool_client.expect_is_alive().once().return_once(|| Ok(()));
+    let mempool_client: SharedMempoolClient = Arc::new(mock_mempool_client);
+    let mut mock_state_reader_factory = MockStateReaderFactory::new();
+    mock_state_reader_factory.expect_get_state_reader_from_latest_block().once().returning(|| {
+        std::thread::sleep(Duration::from_millis(200));
+        local_test_state_reader_factory(CairoVersion::Cairo1, false)
+            .get_state_reader_from_latest_block()
+    });
+    let timeout = Duration::from_millis(10);
+
+    let report =
+        HealthReport::check(&mempool_client, Arc::new(mock_state_reader_factory), timeout).await;
+
+    assert_eq!(
+        report,
+        HealthReport {
+            mempool: DependencyHealth::Healthy,
+            state_reader: DependencyHealth::Unhealthy {
+                error: format!("The health check timed out after {timeout:?}.")
+            },
+        }
+    );
 }
 
 async fn to_bytes(res: Response) -> Bytes {
     res.into_body().collect().await.unwrap().to_bytes()
 }
 
+async fn json_rpc_response(response: Response) -> JsonRpcResponse {
+    assert_eq!(response.status(), StatusCode::OK);
+    serde_json::from_slice(&to_bytes(response).await).unwrap()
+}
+
 fn calculate_hash(external_tx: &RPCTransaction) -> TransactionHash {
     let optional_class_info = match &external_tx {
         RPCTransaction::Declare(_declare_tx) => {
@@ -0,0 +1,85 @@
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use starknet_mempool_types::communication::SharedMempoolClient;
+
+use crate::state_reader::StateReaderFactory;
+
+/// The time each dependency is given to respond to a health check, before it is deemed unhealthy.
+pub const DEPENDENCY_CHECK_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// The health of a dependency the gateway requires for serving transactions.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum DependencyHealth {
+    Healthy,
+    Unhealthy { error: String },
+}
+
+impl DependencyHealth {
+    fn from_result<E: ToString>(result: Result<(), E>) -> Self {
+        match result {
+            Ok(()) => DependencyHealth::Healthy,
+            Err(error) => DependencyHealth::Unhealthy { error: error.to_string() },
+        }
+    }
+}
+
+/// The health of each of the gateway's dependencies; the gateway is ready to serve transactions
+/// only if all of them are healthy.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct HealthReport {
+    pub mempool: DependencyHealth,
+    pub state_reader: DependencyHealth,
+}
+
+impl HealthReport {
+    /// Checks the dependencies concurrently; a dependency that does not respond within the given
+    /// timeout is deemed unhealthy.
+    pub async fn check(
+        mempool_client: &SharedMempoolClient,
+        state_reader_factory: Arc<dyn StateReaderFactory>,
+        timeout: Duration,
+    ) -> Self {
+        let (mempool, state_reader) = tokio::join!(
+            check_with_timeout(check_mempool(mempool_client), timeout),
+            check_with_timeout(check_state_reader(state_reader_factory), timeout)
+        );
+        HealthReport { mempool, state_reader }
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.mempool == DependencyHealth::Healthy && self.state_reader == DependencyHealth::Healthy
+    }
+}
+
+async fn check_with_timeout(
+    check: impl Future<Output = DependencyHealth>,
+    timeout: Duration,
+) -> DependencyHealth {
+    tokio::time::timeout(timeout, check).await.unwrap_or_else(|_elapsed| {
+        DependencyHealth::Unhealthy {
+            error: format!("The health check timed out after {timeout:?}."),
+        }
+    })
+}
+
+async fn check_mempool(mempool_client: &SharedMempoolClient) -> DependencyHealth {
+    DependencyHealth::from_result(mempool_client.is_alive().await)
+}
+
+async fn check_state_reader(state_reader_factory: Arc<dyn StateReaderFactory>) -> DependencyHealth {
+    // The state reader blocks on its requests; on timeout, the request is left to complete in the
+    // background.
+    let result = tokio::task::spawn_blocking(move || {
+        state_reader_factory.get_state_reader_from_latest_block().get_block_info()
+    })
+    .await;
+
+    match result {
+        Ok(result) => DependencyHealth::from_result(result.map(|_block_info| ())),
+        Err(join_error) => DependencyHealth::Unhealthy { error: join_error.to_string() },
+    }
+}
@@ -0,0 +1,156 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use starknet_api::rpc_transaction::RPCTransaction;
+
+use crate::errors::GatewayError;
+use crate::rpc_objects::RpcSpecError;
+
+#[cfg(test)]
+#[path = "json_rpc_test.rs"]
+mod json_rpc_test;
+
+pub const JSON_RPC_VERSION: &str = "2.0";
+
+// JSON-RPC 2.0 error codes.
+pub const PARSE_ERROR: i32 = -32700;
+pub const INVALID_REQUEST: i32 = -32600;
+pub const METHOD_NOT_FOUND: i32 = -32601;
+pub const INVALID_PARAMS: i32 = -32602;
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct JsonRpcRequest {
+    pub jsonrpc: String,
+    pub method: String,
+    #[serde(default)]
+    pub params: Value,
+    #[serde(default)]
+    pub id: Value,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct JsonRpcResponse {
+    pub jsonrpc: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<JsonRpcError>,
+    pub id: Value,
+}
+
+impl JsonRpcResponse {
+    pub fn new(id: Value, result: Result<Value, JsonRpcError>) -> Self {
+        let (result, error) = match result {
+            Ok(result) => (Some(result), None),
+            Err(error) => (None, Some(error)),
+        };
+        JsonRpcResponse { jsonrpc: JSON_RPC_VERSION.to_string(), result, error, id }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct JsonRpcError {
+    pub code: i32,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+impl JsonRpcError {
+    pub fn parse_error(error: impl ToString) -> Self {
+        JsonRpcError::new(PARSE_ERROR, "Parse error", error)
+    }
+
+    pub fn invalid_request(error: impl ToString) -> Self {
+        JsonRpcError::new(INVALID_REQUEST, "Invalid request", error)
+    }
+
+    pub fn method_not_found(method: &str) -> Self {
+        JsonRpcError::new(METHOD_NOT_FOUND, "Method not found", method)
+    }
+
+    pub fn invalid_params(error: impl ToString) -> Self {
+        JsonRpcError::new(INVALID_PARAMS, "Invalid params", error)
+    }
+
+    fn new(code: i32, message: &str, data: impl ToString) -> Self {
+        JsonRpcError {
+            code,
+            message: message.to_string(),
+            data: Some(Value::String(data.to_string())),
+        }
+    }
+}
+
+impl From<GatewayError> for JsonRpcError {
+    fn from(error: GatewayError) -> Self {
+        let (_, RpcSpecError { code, message, data }) = error.rpc_spec_error();
+        JsonRpcError { code: code.into(), message, data }
+    }
+}
+
+/// The write methods of the Starknet spec's API served by the gateway.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AddTransactionMethod {
+    Declare,
+    DeployAccount,
+    Invoke,
+}
+
+impl AddTransactionMethod {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "starknet_addDeclareTransaction" => Some(AddTransactionMethod::Declare),
+            "starknet_addDeployAccountTransaction" => Some(AddTransactionMethod::DeployAccount),
+            "starknet_addInvokeTransaction" => Some(AddTransactionMethod::Invoke),
+            _ => None,
+        }
+    }
+
+    /// Parses the transaction from the method's params, given either by name or by position.
+    pub fn parse_params(&self, params: Value) -> Result<RPCTransaction, JsonRpcError> {
+        let tx = match params {
+            Value::Object(mut params) if params.len() == 1 => {
+                params.remove(self.param_name()).ok_or_else(|| {
+                    JsonRpcError::invalid_params(format!("Missing param: {}.", self.param_name()))
+                })?
+            }
+            Value::Array(params) if params.len() == 1 => {
+                params.into_iter().next().expect("Params should hold a single element.")
+            }
+            _ => {
+                return Err(JsonRpcError::invalid_params(format!(
+                    "Expected a single param: {}.",
+                    self.param_name()
+                )));
+            }
+        };
+
+        let tx: RPCTransaction =
+            serde_json::from_value(tx).map_err(JsonRpcError::invalid_params)?;
+        if !self.accepts(&tx) {
+            return Err(JsonRpcError::invalid_params(format!(
+                "The transaction type does not match the method's type: {}.",
+                self.param_name()
+            )));
+        }
+
+        Ok(tx)
+    }
+
+    fn param_name(&self) -> &'static str {
+        match self {
+            AddTransactionMethod::Declare => "declare_transaction",
+            AddTransactionMethod::DeployAccount => "deploy_account_transaction",
+            AddTransactionMethod::Invoke => "invoke_transaction",
+        }
+    }
+
+    fn accepts(&self, tx: &RPCTransaction) -> bool {
+        matches!(
+            (self, tx),
+            (AddTransactionMethod::Declare, RPCTransaction::Declare(_))
+                | (AddTransactionMethod::DeployAccount, RPCTransaction::DeployAccount(_))
+                | (AddTransactionMethod::Invoke, RPCTransaction::Invoke(_))
+        )
+    }
+}
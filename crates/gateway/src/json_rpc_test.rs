@@ -0,0 +1,68 @@
+use blockifier::test_utils::CairoVersion;
+use mempool_test_utils::starknet_api_test_utils::invoke_tx;
+use rstest::rstest;
+use serde_json::{json, Value};
+use starknet_mempool_infra::component_client::definitions::ClientError;
+use starknet_mempool_types::communication::MempoolClientError;
+
+use crate::errors::{GatewayError, GatewaySpecError};
+use crate::json_rpc::{AddTransactionMethod, JsonRpcError, INVALID_PARAMS};
+
+#[rstest]
+#[case::named(json!({"invoke_transaction": invoke_tx(CairoVersion::Cairo1)}))]
+#[case::positional(json!([invoke_tx(CairoVersion::Cairo1)]))]
+fn test_parse_params(#[case] params: Value) {
+    let tx = AddTransactionMethod::Invoke.parse_params(params).unwrap();
+    assert_eq!(tx, invoke_tx(CairoVersion::Cairo1));
+}
+
+#[rstest]
+#[case::no_params(AddTransactionMethod::Invoke, Value::Null)]
+#[case::wrong_param_name(
+    AddTransactionMethod::Invoke,
+    json!({"declare_transaction": invoke_tx(CairoVersion::Cairo1)})
+)]
+#[case::too_many_params(
+    AddTransactionMethod::Invoke,
+    json!([invoke_tx(CairoVersion::Cairo1), invoke_tx(CairoVersion::Cairo1)])
+)]
+#[case::malformed_tx(AddTransactionMethod::Invoke, json!([{"type": "INVOKE"}]))]
+#[case::mismatched_tx_type(
+    AddTransactionMethod::Declare,
+    json!([invoke_tx(CairoVersion::Cairo1)])
+)]
+fn test_parse_invalid_params(#[case] method: AddTransactionMethod, #[case] params: Value) {
+    let error = method.parse_params(params).unwrap_err();
+    assert_eq!(error.code, INVALID_PARAMS);
+}
+
+#[rstest]
+#[case::declare("starknet_addDeclareTransaction", Some(AddTransactionMethod::Declare))]
+#[case::deploy_account(
+    "starknet_addDeployAccountTransaction",
+    Some(AddTransactionMethod::DeployAccount)
+)]
+#[case::invoke("starknet_addInvokeTransaction", Some(AddTransactionMethod::Invoke))]
+#[case::unknown("starknet_getNonce", None)]
+fn test_method_from_name(
+    #[case] name: &str,
+    #[case] expected_method: Option<AddTransactionMethod>,
+) {
+    assert_eq!(AddTransactionMethod::from_name(name), expected_method);
+}
+
+#[test]
+fn test_json_rpc_error_from_server_error() {
+    let error: GatewayError =
+        MempoolClientError::ClientError(ClientError::UnexpectedResponse).into();
+
+    let json_rpc_error = JsonRpcError::from(error);
+
+    let spec_error = GatewaySpecError::UnexpectedError;
+    let expected_json_rpc_error = JsonRpcError {
+        code: spec_error.code().into(),
+        message: spec_error.message().to_string(),
+        data: None,
+    };
+    assert_eq!(json_rpc_error, expected_json_rpc_error);
+}
@@ -1,9 +1,12 @@
+mod admission_control;
 pub mod communication;
 mod compilation;
 mod compiler_version;
 pub mod config;
 pub mod errors;
 pub mod gateway;
+mod health;
+mod json_rpc;
 mod rpc_objects;
 mod rpc_state_reader;
 #[cfg(test)]
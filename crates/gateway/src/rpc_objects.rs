@@ -120,8 +120,10 @@ pub struct RpcErrorResponse {
     pub error: RpcSpecError,
 }
 
-#[derive(Serialize, Deserialize, Debug, Default)]
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
 pub struct RpcSpecError {
     pub code: u16,
     pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
 }
@@ -4,13 +4,16 @@ use blockifier::bouncer::BouncerConfig;
 use blockifier::context::BlockContext;
 use blockifier::execution::contract_class::ClassInfo;
 use blockifier::state::cached_state::CachedState;
+use blockifier::state::state_api::StateReader;
 use blockifier::transaction::account_transaction::AccountTransaction;
 use blockifier::versioned_constants::VersionedConstants;
 #[cfg(test)]
 use mockall::automock;
+use num_traits::ToPrimitive;
 use starknet_api::core::{ContractAddress, Nonce};
 use starknet_api::rpc_transaction::{RPCInvokeTransaction, RPCTransaction};
 use starknet_api::transaction::TransactionHash;
+use starknet_mempool_types::mempool_types::AccountState;
 use starknet_types_core::felt::Felt;
 
 use crate::config::StatefulTransactionValidatorConfig;
@@ -67,12 +70,35 @@ impl StatefulTransactionValidator {
             &self.config.chain_info.chain_id,
         )?;
         let tx_hash = get_tx_hash(&account_tx);
-        let account_nonce = validator.get_nonce(get_sender_address(external_tx))?;
+        let account_nonce = validator.get_nonce(get_sender_address(external_tx)?)?;
         let skip_validate = skip_stateful_validations(external_tx, account_nonce);
         validator.validate(account_tx, skip_validate)?;
         Ok(tx_hash)
     }
 
+    /// Returns the nonce and the (STRK) fee token balance of the given account, as of the latest
+    /// block.
+    pub fn get_account_state(
+        &self,
+        state_reader_factory: &dyn StateReaderFactory,
+        account_address: ContractAddress,
+    ) -> StatefulTransactionValidatorResult<AccountState> {
+        let mut state_reader = state_reader_factory.get_state_reader_from_latest_block();
+        let nonce = state_reader.get_nonce_at(account_address)?;
+        let (balance_low, balance_high) = state_reader.get_fee_token_balance(
+            account_address,
+            self.config.chain_info.strk_fee_token_address,
+        )?;
+        // Balances exceeding 128 bits are capped, as they cover any transaction fee.
+        let balance = if balance_high == Felt::ZERO {
+            balance_low.to_u128().unwrap_or(u128::MAX)
+        } else {
+            u128::MAX
+        };
+
+        Ok(AccountState { nonce, balance })
+    }
+
     pub fn instantiate_validator(
         &self,
         state_reader_factory: &dyn StateReaderFactory,
@@ -104,12 +130,12 @@ impl StatefulTransactionValidator {
 // Check if validation of an invoke transaction should be skipped due to deploy_account not being
 // proccessed yet. This feature is used to improve UX for users sending deploy_account + invoke at
 // once.
-fn skip_stateful_validations(tx: &RPCTransaction, account_nonce: Nonce) -> bool {
+pub fn skip_stateful_validations(tx: &RPCTransaction, account_nonce: Nonce) -> bool {
     match tx {
         RPCTransaction::Invoke(RPCInvokeTransaction::V3(tx)) => {
             // check if the transaction nonce is 1, meaning it is post deploy_account, and the
-            // account nonce is zero, meaning the account was not deployed yet. The mempool also
-            // verifies that the deploy_account transaction exists.
+            // account nonce is zero, meaning the account was not deployed yet. The mempool only
+            // accepts such a transaction if the account's deploy_account transaction is pending.
             tx.nonce == Nonce(Felt::ONE) && account_nonce == Nonce(Felt::ZERO)
         }
         RPCTransaction::DeployAccount(_) | RPCTransaction::Declare(_) => false,
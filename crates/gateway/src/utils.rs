@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use blockifier::execution::contract_class::ClassInfo;
 use blockifier::transaction::account_transaction::AccountTransaction;
 use blockifier::transaction::transactions::{
@@ -6,14 +8,22 @@ use blockifier::transaction::transactions::{
     InvokeTransaction as BlockifierInvokeTransaction,
 };
 use starknet_api::core::{calculate_contract_address, ChainId, ClassHash, ContractAddress, Nonce};
+use starknet_api::hash::starknet_keccak_hash;
 use starknet_api::rpc_transaction::{
-    RPCDeclareTransaction, RPCDeployAccountTransaction, RPCInvokeTransaction, RPCTransaction,
+    ContractClass, RPCDeclareTransaction, RPCDeployAccountTransaction, RPCInvokeTransaction,
+    RPCTransaction,
 };
+use starknet_api::state::EntryPoint;
 use starknet_api::transaction::{
     DeclareTransaction, DeclareTransactionV3, DeployAccountTransaction, DeployAccountTransactionV3,
     InvokeTransaction, InvokeTransactionV3, Tip, TransactionHash, TransactionHasher,
 };
-use starknet_mempool_types::mempool_types::ThinTransaction;
+use starknet_api::StarknetApiError;
+use starknet_mempool_types::mempool_types::{
+    CompiledClassInfo, ExecutableTransaction, ThinTransaction,
+};
+use starknet_types_core::felt::Felt;
+use starknet_types_core::hash::{Poseidon, StarkHash};
 
 use crate::errors::StatefulTransactionValidatorResult;
 
@@ -47,28 +57,68 @@ impl RPCTransactionExt for RPCTransaction {
 }
 
 pub fn external_tx_to_thin_tx(
-    external_tx: &RPCTransaction,
+    external_tx: RPCTransaction,
     tx_hash: TransactionHash,
-) -> ThinTransaction {
-    ThinTransaction {
+    compiled_class_info: Option<CompiledClassInfo>,
+) -> StatefulTransactionValidatorResult<ThinTransaction> {
+    Ok(ThinTransaction {
         tip: *external_tx.tip(),
         nonce: *external_tx.nonce(),
-        sender_address: get_sender_address(external_tx),
+        sender_address: get_sender_address(&external_tx)?,
         tx_hash,
-    }
+        resource_bounds: external_tx.resource_bounds().clone(),
+        executable_tx: Arc::new(ExecutableTransaction { tx: external_tx, compiled_class_info }),
+    })
 }
 
-pub fn get_sender_address(tx: &RPCTransaction) -> ContractAddress {
+/// Returns the address of the account sending the transaction; for a deploy account transaction,
+/// this is the address of the deployed account.
+pub fn get_sender_address(
+    tx: &RPCTransaction,
+) -> StatefulTransactionValidatorResult<ContractAddress> {
     match tx {
-        RPCTransaction::Declare(RPCDeclareTransaction::V3(tx)) => tx.sender_address,
-        // TODO(Mohammad): Add support for deploy account.
-        RPCTransaction::DeployAccount(RPCDeployAccountTransaction::V3(_)) => {
-            ContractAddress::default()
+        RPCTransaction::Declare(RPCDeclareTransaction::V3(tx)) => Ok(tx.sender_address),
+        RPCTransaction::DeployAccount(RPCDeployAccountTransaction::V3(tx)) => {
+            Ok(calculate_contract_address(
+                tx.contract_address_salt,
+                tx.class_hash,
+                &tx.constructor_calldata,
+                ContractAddress::default(),
+            )?)
         }
-        RPCTransaction::Invoke(RPCInvokeTransaction::V3(tx)) => tx.sender_address,
+        RPCTransaction::Invoke(RPCInvokeTransaction::V3(tx)) => Ok(tx.sender_address),
     }
 }
 
+/// Computes the hash of a Sierra contract class, as defined by the Starknet protocol.
+pub fn compute_class_hash(
+    contract_class: &ContractClass,
+) -> StatefulTransactionValidatorResult<ClassHash> {
+    let version = format!("CONTRACT_CLASS_V{}", contract_class.contract_class_version);
+    // The version is encoded as a Cairo short string, which holds at most 31 characters.
+    if version.len() > 31 {
+        return Err(StarknetApiError::OutOfRange { string: version }.into());
+    }
+    let entry_points = &contract_class.entry_points_by_type;
+
+    Ok(ClassHash(Poseidon::hash_array(&[
+        Felt::from_bytes_be_slice(version.as_bytes()),
+        hash_entry_points(&entry_points.external),
+        hash_entry_points(&entry_points.l1handler),
+        hash_entry_points(&entry_points.constructor),
+        starknet_keccak_hash(contract_class.abi.as_bytes()),
+        Poseidon::hash_array(&contract_class.sierra_program),
+    ])))
+}
+
+fn hash_entry_points(entry_points: &[EntryPoint]) -> Felt {
+    let entry_point_felts: Vec<Felt> = entry_points
+        .iter()
+        .flat_map(|entry_point| [entry_point.selector.0, Felt::from(entry_point.function_idx.0)])
+        .collect();
+    Poseidon::hash_array(&entry_point_felts)
+}
+
 // TODO(Mohammad): Remove this trait once it is implemented in StarkNet API.
 #[allow(dead_code)]
 pub trait RPCTransactionExt {
@@ -117,12 +167,7 @@ pub fn external_tx_to_account_tx(
                 fee_data_availability_mode: tx.fee_data_availability_mode,
                 paymaster_data: tx.paymaster_data.clone(),
             });
-            let contract_address = calculate_contract_address(
-                deploy_account_tx.contract_address_salt(),
-                deploy_account_tx.class_hash(),
-                &deploy_account_tx.constructor_calldata(),
-                ContractAddress::default(),
-            )?;
+            let contract_address = get_sender_address(external_tx)?;
             let tx_hash = deploy_account_tx
                 .calculate_transaction_hash(chain_id, &deploy_account_tx.version())?;
             let deploy_account_tx = BlockifierDeployAccountTransaction::new(
@@ -1,7 +1,13 @@
+use assert_matches::assert_matches;
+use mempool_test_utils::starknet_api_test_utils::declare_tx;
 use pretty_assertions::assert_eq;
 use rstest::rstest;
+use starknet_api::core::ClassHash;
+use starknet_api::rpc_transaction::{ContractClass, RPCDeclareTransaction, RPCTransaction};
+use starknet_api::{felt, StarknetApiError};
 
-use crate::utils::is_subsequence;
+use crate::errors::StatefulTransactionValidatorError;
+use crate::utils::{compute_class_hash, is_subsequence};
 
 #[rstest]
 #[case::empty(
@@ -76,3 +82,59 @@ fn test_is_subsequence(
 ) {
     assert_eq!(is_subsequence(subsequence, sequence), expected_result);
 }
+
+#[test]
+fn test_compute_class_hash() {
+    let declare_tx = assert_matches!(
+        declare_tx(),
+        RPCTransaction::Declare(RPCDeclareTransaction::V3(declare_tx)) => declare_tx
+    );
+
+    assert_eq!(
+        compute_class_hash(&declare_tx.contract_class).unwrap(),
+        ClassHash(felt!("0x65fb762d56c837eb286feaca2427c97c312d5785376454c273165c867c14eae"))
+    );
+}
+
+#[test]
+fn test_compute_class_hash_of_empty_class() {
+    // Hashes an empty ABI, and empty entry point and program lists.
+    assert_eq!(
+        compute_class_hash(&ContractClass::default()).unwrap(),
+        ClassHash(felt!("0x63d52a9f62c944436768aaba50f70ffacc64ba807bb381c05b6c36794060180"))
+    );
+}
+
+#[rstest]
+// The version is prefixed by "CONTRACT_CLASS_V", leaving 15 characters of the 31-character short
+// string.
+#[case::longest_version("0.1.0-123456789", true)]
+#[case::overlong_version("0.1.0-1234567890", false)]
+fn test_compute_class_hash_version_length(#[case] version: &str, #[case] is_valid: bool) {
+    let contract_class =
+        ContractClass { contract_class_version: version.to_string(), ..Default::default() };
+
+    let result = compute_class_hash(&contract_class);
+
+    if is_valid {
+        assert_matches!(result, Ok(_));
+    } else {
+        assert_matches!(
+            result,
+            Err(StatefulTransactionValidatorError::StarknetApiError(
+                StarknetApiError::OutOfRange { string }
+            )) if string == format!("CONTRACT_CLASS_V{version}")
+        );
+    }
+}
+
+#[test]
+fn test_compute_class_hash_hashes_abi_verbatim() {
+    let contract_class = ContractClass { abi: "[]".to_string(), ..Default::default() };
+    let reformatted_contract_class = ContractClass { abi: "[ ]".to_string(), ..Default::default() };
+
+    assert_ne!(
+        compute_class_hash(&contract_class).unwrap(),
+        compute_class_hash(&reformatted_contract_class).unwrap()
+    );
+}
@@ -0,0 +1,76 @@
+use std::collections::HashMap;
+
+use starknet_api::core::ContractAddress;
+use starknet_api::transaction::TransactionHash;
+use starknet_mempool_types::mempool_types::ThinTransaction;
+
+/// Tracks, per account, its latest known fee token balance and the sum of the maximal fees of
+/// its transactions held by the mempool, including transactions handed out for sequencing that
+/// were not yet committed.
+#[derive(Debug, Default)]
+pub struct AccountFeeTracker {
+    balances: HashMap<ContractAddress, u128>,
+    committed_fees: HashMap<ContractAddress, u128>,
+    tx_fees: HashMap<TransactionHash, (ContractAddress, u128)>,
+}
+
+impl AccountFeeTracker {
+    pub fn set_balance(&mut self, address: ContractAddress, balance: u128) {
+        self.balances.insert(address, balance);
+    }
+
+    pub fn balance(&self, address: ContractAddress) -> u128 {
+        self.balances.get(&address).copied().unwrap_or_default()
+    }
+
+    /// Forgets the balances of the accounts not satisfying the given predicate.
+    pub fn retain_balances(&mut self, mut predicate: impl FnMut(&ContractAddress) -> bool) {
+        self.balances.retain(|address, _| predicate(address));
+    }
+
+    /// Checks whether the account, given its balance, can cover the given fee on top of the fees
+    /// of its other transactions, excluding the one being replaced (if any).
+    pub fn can_afford(
+        &self,
+        address: ContractAddress,
+        balance: u128,
+        fee: u128,
+        replaced_tx_hash: Option<TransactionHash>,
+    ) -> bool {
+        let committed_fees = self.committed_fees.get(&address).copied().unwrap_or_default();
+        let replaced_tx_fee = replaced_tx_hash
+            .and_then(|tx_hash| self.tx_fees.get(&tx_hash))
+            .map_or(0, |&(_, fee)| fee);
+
+        committed_fees.saturating_sub(replaced_tx_fee).saturating_add(fee) <= balance
+    }
+
+    pub fn insert(&mut self, tx_hash: TransactionHash, address: ContractAddress, fee: u128) {
+        if self.tx_fees.insert(tx_hash, (address, fee)).is_none() {
+            let committed_fees = self.committed_fees.entry(address).or_default();
+            *committed_fees = committed_fees.saturating_add(fee);
+        }
+    }
+
+    /// Releases the fee of a transaction that no longer needs to be covered by its account.
+    pub fn remove(&mut self, tx_hash: TransactionHash) {
+        let Some((address, fee)) = self.tx_fees.remove(&tx_hash) else {
+            return;
+        };
+
+        if let Some(committed_fees) = self.committed_fees.get_mut(&address) {
+            *committed_fees = committed_fees.saturating_sub(fee);
+            if *committed_fees == 0 {
+                self.committed_fees.remove(&address);
+            }
+        }
+    }
+}
+
+/// Returns the maximal fee the transaction may be charged, according to its resource bounds.
+pub fn max_fee(tx: &ThinTransaction) -> u128 {
+    [tx.resource_bounds.l1_gas, tx.resource_bounds.l2_gas]
+        .iter()
+        .map(|bounds| u128::from(bounds.max_amount).saturating_mul(bounds.max_price_per_unit))
+        .fold(0, u128::saturating_add)
+}
@@ -1,11 +1,18 @@
+use std::sync::{Arc, Mutex, MutexGuard};
+
 use async_trait::async_trait;
+use starknet_api::transaction::TransactionHash;
 use starknet_mempool_infra::component_definitions::ComponentRequestHandler;
 use starknet_mempool_infra::component_runner::ComponentStarter;
 use starknet_mempool_infra::component_server::local_component_server::LocalComponentServer;
 use starknet_mempool_types::communication::{
     MempoolRequest, MempoolRequestAndResponseSender, MempoolResponse,
 };
-use starknet_mempool_types::mempool_types::{MempoolInput, MempoolResult, ThinTransaction};
+use starknet_mempool_types::mempool_types::{
+    CommitBlockArgs, MempoolEvent, MempoolInput, MempoolResult, ResourceBudget, ThinTransaction,
+    TransactionStatus,
+};
+use tokio::sync::broadcast;
 use tokio::sync::mpsc::Receiver;
 
 use crate::mempool::Mempool;
@@ -13,8 +20,10 @@ use crate::mempool::Mempool;
 pub type MempoolServer =
     LocalComponentServer<MempoolCommunicationWrapper, MempoolRequest, MempoolResponse>;
 
+/// Creates the mempool's server. The mempool is shared with its snapshot writer (see
+/// `snapshot::write_snapshots`), if snapshots are enabled.
 pub fn create_mempool_server(
-    mempool: Mempool,
+    mempool: Arc<Mutex<Mempool>>,
     rx_mempool: Receiver<MempoolRequestAndResponseSender>,
 ) -> MempoolServer {
     let communication_wrapper = MempoolCommunicationWrapper::new(mempool);
@@ -23,34 +32,84 @@ pub fn create_mempool_server(
 
 /// Wraps the mempool to enable inbound async communication from other components.
 pub struct MempoolCommunicationWrapper {
-    mempool: Mempool,
+    mempool: Arc<Mutex<Mempool>>,
 }
 
 impl MempoolCommunicationWrapper {
-    pub fn new(mempool: Mempool) -> Self {
+    pub fn new(mempool: Arc<Mutex<Mempool>>) -> Self {
         MempoolCommunicationWrapper { mempool }
     }
 
+    fn mempool(&self) -> MutexGuard<'_, Mempool> {
+        self.mempool.lock().expect("The mempool lock should not be poisoned.")
+    }
+
     fn add_tx(&mut self, mempool_input: MempoolInput) -> MempoolResult<()> {
-        self.mempool.add_tx(mempool_input)
+        self.mempool().add_tx(mempool_input)
     }
 
     fn get_txs(&mut self, n_txs: usize) -> MempoolResult<Vec<ThinTransaction>> {
-        self.mempool.get_txs(n_txs)
+        self.mempool().get_txs(n_txs)
+    }
+
+    fn get_txs_with_budget(
+        &mut self,
+        budget: ResourceBudget,
+    ) -> MempoolResult<Vec<ThinTransaction>> {
+        self.mempool().get_txs_with_budget(budget)
+    }
+
+    fn return_txs(&mut self, txs: Vec<ThinTransaction>) -> MempoolResult<()> {
+        self.mempool().return_txs(txs)
+    }
+
+    fn commit_block(&mut self, args: CommitBlockArgs) -> MempoolResult<()> {
+        self.mempool().commit_block(args)
+    }
+
+    fn get_tx_status(&self, tx_hash: TransactionHash) -> MempoolResult<TransactionStatus> {
+        self.mempool().get_tx_status(tx_hash)
+    }
+
+    fn subscribe(&self) -> MempoolResult<broadcast::Receiver<MempoolEvent>> {
+        Ok(self.mempool().subscribe())
+    }
+
+    fn get_metrics(&self) -> MempoolResult<String> {
+        Ok(self.mempool().metrics())
     }
 }
 
 #[async_trait]
 impl ComponentRequestHandler<MempoolRequest, MempoolResponse> for MempoolCommunicationWrapper {
     async fn handle_request(&mut self, request: MempoolRequest) -> MempoolResponse {
-        match request {
+        let response = match request {
             MempoolRequest::AddTransaction(mempool_input) => {
                 MempoolResponse::AddTransaction(self.add_tx(mempool_input))
             }
             MempoolRequest::GetTransactions(n_txs) => {
                 MempoolResponse::GetTransactions(self.get_txs(n_txs))
             }
-        }
+            MempoolRequest::GetTransactionsWithBudget(budget) => {
+                MempoolResponse::GetTransactionsWithBudget(self.get_txs_with_budget(budget))
+            }
+            MempoolRequest::ReturnTransactions(txs) => {
+                MempoolResponse::ReturnTransactions(self.return_txs(txs))
+            }
+            MempoolRequest::CommitBlock(args) => {
+                MempoolResponse::CommitBlock(self.commit_block(args))
+            }
+            MempoolRequest::GetTransactionStatus(tx_hash) => {
+                MempoolResponse::GetTransactionStatus(self.get_tx_status(tx_hash))
+            }
+            MempoolRequest::Subscribe => MempoolResponse::Subscribe(self.subscribe()),
+            MempoolRequest::GetMetrics => MempoolResponse::GetMetrics(self.get_metrics()),
+            // Liveness probes are answered without accessing the mempool.
+            MempoolRequest::IsAlive => return MempoolResponse::IsAlive(Ok(())),
+        };
+        self.mempool().check_invariants_if_enabled();
+
+        response
     }
 }
 
@@ -0,0 +1,188 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use papyrus_config::converters::deserialize_seconds_to_duration;
+use papyrus_config::dumping::{ser_optional_sub_config, ser_param, SerializeConfig};
+use papyrus_config::{ParamPath, ParamPrivacyInput, SerializedParam};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::ordering::TransactionOrderingPolicy;
+
+/// The mempool related configuration.
+#[derive(Clone, Debug, Serialize, Deserialize, Validate, PartialEq)]
+pub struct MempoolConfig {
+    // The minimal percentage by which a transaction must raise both its tip and its resource
+    // bound prices in order to replace an existing transaction with the same sender and nonce.
+    pub fee_escalation_percentage: u8,
+    // The maximal number of transactions held in the mempool.
+    pub max_txs: usize,
+    // The maximal (estimated) total size of the transactions held in the mempool, in bytes.
+    pub max_size_in_bytes: usize,
+    // The maximal number of transactions of a single account held in the mempool.
+    pub max_txs_per_account: usize,
+    // The maximal distance of a transaction's nonce ahead of its account's nonce.
+    pub max_nonce_distance: u64,
+    // The time after which a transaction that was not sequenced is removed from the mempool.
+    #[serde(deserialize_with = "deserialize_seconds_to_duration")]
+    pub tx_ttl: Duration,
+    // The policy by which eligible transactions are prioritized for sequencing.
+    pub tx_ordering_policy: TransactionOrderingPolicy,
+    // Persistence of the mempool's transactions across restarts; disabled if not provided.
+    pub snapshot_config: Option<MempoolSnapshotConfig>,
+    // Whether to check the consistency of the mempool's internal state after each request; meant
+    // for debugging, as the check traverses all transactions.
+    pub check_invariants: bool,
+    // Spreads the transactions retrieved in a single call across accounts; disabled if not
+    // provided.
+    pub fairness_config: Option<FairnessConfig>,
+}
+
+impl Default for MempoolConfig {
+    fn default() -> Self {
+        MempoolConfig {
+            fee_escalation_percentage: 10,
+            max_txs: 100_000,
+            max_size_in_bytes: 100_000_000,
+            max_txs_per_account: 64,
+            max_nonce_distance: 128,
+            tx_ttl: Duration::from_secs(60 * 60),
+            tx_ordering_policy: TransactionOrderingPolicy::default(),
+            snapshot_config: None,
+            check_invariants: false,
+            fairness_config: None,
+        }
+    }
+}
+
+impl SerializeConfig for MempoolConfig {
+    fn dump(&self) -> BTreeMap<ParamPath, SerializedParam> {
+        let params = BTreeMap::from_iter([
+            ser_param(
+                "fee_escalation_percentage",
+                &self.fee_escalation_percentage,
+                "Minimal percentage increase of the tip and resource bound prices required for a \
+                 transaction to replace an existing transaction with the same sender and nonce.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "max_txs",
+                &self.max_txs,
+                "Maximal number of transactions held in the mempool.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "max_size_in_bytes",
+                &self.max_size_in_bytes,
+                "Maximal estimated total size of the transactions held in the mempool, in bytes.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "max_txs_per_account",
+                &self.max_txs_per_account,
+                "Maximal number of transactions of a single account held in the mempool.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "max_nonce_distance",
+                &self.max_nonce_distance,
+                "Maximal distance of a transaction's nonce ahead of its account's nonce.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "tx_ttl",
+                &self.tx_ttl.as_secs(),
+                "Time, in seconds, after which a transaction that was not sequenced is removed \
+                 from the mempool.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "check_invariants",
+                &self.check_invariants,
+                "Whether to check the consistency of the mempool's internal state after each \
+                 request; meant for debugging, as the check traverses all transactions.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "tx_ordering_policy",
+                &self.tx_ordering_policy,
+                "Policy by which eligible transactions are prioritized for sequencing. One of: \
+                 Tip, EffectiveL2GasPrice, TipPerGas, Fifo.",
+                ParamPrivacyInput::Public,
+            ),
+        ]);
+
+        params
+            .into_iter()
+            .chain(ser_optional_sub_config(&self.snapshot_config, "snapshot_config"))
+            .chain(ser_optional_sub_config(&self.fairness_config, "fairness_config"))
+            .collect()
+    }
+}
+
+/// The configuration of the mempool's snapshots, used to restore its transactions after a restart.
+#[derive(Clone, Debug, Serialize, Deserialize, Validate, PartialEq)]
+pub struct MempoolSnapshotConfig {
+    // The file to which snapshots are written, and from which the mempool is restored on startup.
+    pub path: PathBuf,
+    // The time between consecutive snapshots.
+    #[serde(deserialize_with = "deserialize_seconds_to_duration")]
+    pub flush_interval: Duration,
+}
+
+impl Default for MempoolSnapshotConfig {
+    fn default() -> Self {
+        MempoolSnapshotConfig {
+            path: PathBuf::from("data/mempool_snapshot.json"),
+            flush_interval: Duration::from_secs(60),
+        }
+    }
+}
+
+impl SerializeConfig for MempoolSnapshotConfig {
+    fn dump(&self) -> BTreeMap<ParamPath, SerializedParam> {
+        BTreeMap::from_iter([
+            ser_param(
+                "path",
+                &self.path,
+                "The file to which mempool snapshots are written, and from which the mempool is \
+                 restored on startup.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "flush_interval",
+                &self.flush_interval.as_secs(),
+                "Time, in seconds, between consecutive mempool snapshots.",
+                ParamPrivacyInput::Public,
+            ),
+        ])
+    }
+}
+
+/// The configuration of the mempool's fairness mode, in which the transactions retrieved in a
+/// single call are spread across accounts, and transactions of equal priority are retrieved by
+/// their arrival (rather than by their hash).
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Validate, PartialEq, Eq)]
+pub struct FairnessConfig {
+    // The maximal number of transactions of a single account retrieved in a single call.
+    pub max_txs_per_account: usize,
+}
+
+impl Default for FairnessConfig {
+    fn default() -> Self {
+        FairnessConfig { max_txs_per_account: 4 }
+    }
+}
+
+impl SerializeConfig for FairnessConfig {
+    fn dump(&self) -> BTreeMap<ParamPath, SerializedParam> {
+        BTreeMap::from_iter([ser_param(
+            "max_txs_per_account",
+            &self.max_txs_per_account,
+            "Maximal number of transactions of a single account retrieved from the mempool in a \
+             single call.",
+            ParamPrivacyInput::Public,
+        )])
+    }
+}
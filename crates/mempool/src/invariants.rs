@@ -0,0 +1,48 @@
+use starknet_api::core::{ContractAddress, Nonce};
+use starknet_api::transaction::TransactionHash;
+
+/// A violation of the consistency of the mempool's internal data structures.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum InvariantViolation {
+    /// A transaction in the pool's main mapping is missing from (or differs in) its account
+    /// index.
+    MissingFromAccountIndex { tx_hash: TransactionHash },
+    /// A transaction in the pool's account index is missing from its main mapping.
+    MissingFromMainMapping { tx_hash: TransactionHash },
+    /// A transaction in the pool's account index is held under a wrong account or nonce.
+    MisplacedInAccountIndex { tx_hash: TransactionHash },
+    /// The pool's insertion time index does not match its main mapping.
+    InsertionTimeIndexMismatch { n_indexed_txs: usize, n_txs: usize },
+    /// The tracked total size of the pool's transactions does not match their actual size.
+    SizeMismatch { tracked_size_in_bytes: usize, size_in_bytes: usize },
+    /// The queue's account mapping does not match its priority queue.
+    QueueIndexMismatch { address: ContractAddress },
+    /// A queued transaction is not held in the pool.
+    QueuedTransactionNotInPool { tx_hash: TransactionHash },
+    /// A queued transaction's nonce is not the next nonce of its account.
+    QueuedNonceMismatch {
+        tx_hash: TransactionHash,
+        queued_nonce: Nonce,
+        account_nonce: Option<Nonce>,
+    },
+    /// A transaction with its account's next nonce is held in the pool, but not queued.
+    EligibleTransactionNotQueued { tx_hash: TransactionHash },
+    /// A transaction handed out for sequencing is still held in the pool.
+    StagedTransactionInPool { tx_hash: TransactionHash },
+}
+
+/// The result of checking the mempool's invariants.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct InvariantReport {
+    pub violations: Vec<InvariantViolation>,
+}
+
+impl InvariantReport {
+    pub fn is_ok(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    pub fn report(&mut self, violation: InvariantViolation) {
+        self.violations.push(violation);
+    }
+}
@@ -1,5 +1,12 @@
+pub(crate) mod account_fees;
 pub mod communication;
+pub mod config;
+pub mod invariants;
 pub mod mempool;
+pub mod metrics;
+pub mod ordering;
+pub mod snapshot;
 pub(crate) mod transaction_queue;
 // TODO(Mohammad): change to `pub(crate)`.
 pub mod transaction_pool;
+pub mod utils;
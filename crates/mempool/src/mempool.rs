@@ -1,26 +1,73 @@
-use std::collections::HashMap;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::iter::zip;
+use std::sync::Arc;
+use std::time::Instant;
 
 use starknet_api::core::{ContractAddress, Nonce};
-use starknet_api::transaction::{Tip, TransactionHash};
+use starknet_api::hash::StarkHash;
+use starknet_api::transaction::{ResourceBounds, Tip, TransactionHash};
 use starknet_mempool_types::errors::MempoolError;
 use starknet_mempool_types::mempool_types::{
-    Account, AccountState, MempoolInput, MempoolResult, ThinTransaction,
+    Account, AccountState, CommitBlockArgs, MempoolEvent, MempoolInput, MempoolResult,
+    ResourceBudget, ThinTransaction, TransactionStatus,
 };
+use tokio::sync::broadcast;
+use tracing::{error, warn};
 
-use crate::transaction_pool::TransactionPool;
+use crate::account_fees::{max_fee, AccountFeeTracker};
+use crate::config::MempoolConfig;
+use crate::invariants::{InvariantReport, InvariantViolation};
+use crate::metrics::{MempoolMetrics, PrometheusEncoder};
+use crate::snapshot::{MempoolSnapshot, SnapshotTransaction};
+use crate::transaction_pool::{estimated_tx_size, TransactionPool};
 use crate::transaction_queue::TransactionQueue;
+use crate::utils::{Clock, InstantClock};
 
 #[cfg(test)]
 #[path = "mempool_test.rs"]
 pub mod mempool_test;
 
-#[derive(Debug, Default)]
+#[cfg(test)]
+#[path = "mempool_model_test.rs"]
+mod mempool_model_test;
+
+// The number of transactions exceeding the remaining budget after which a budgeted retrieval ends,
+// bounding its cost once the budget is nearly exhausted.
+const MAX_SKIPPED_TXS_PER_RETRIEVAL: usize = 16;
+
+// The number of events held for subscribers that did not receive them yet; slower subscribers miss
+// the oldest events.
+const EVENT_CHANNEL_CAPACITY: usize = 1024;
+
+#[derive(Debug)]
 pub struct Mempool {
+    config: MempoolConfig,
     // TODO: add docstring explaining visibility and coupling of the fields.
     // All transactions currently held in the mempool.
     tx_pool: TransactionPool,
     // Transactions eligible for sequencing.
     tx_queue: TransactionQueue,
+    // The next nonce of each account, taking into account transactions that were already handed
+    // out for sequencing but not yet committed.
+    account_nonces: HashMap<ContractAddress, Nonce>,
+    // Transactions handed out for sequencing, which were not yet reported as included in or
+    // rejected from a block.
+    staged_txs: HashMap<TransactionHash, TransactionReference>,
+    // The balances of accounts and the fees committed by their transactions.
+    account_fees: AccountFeeTracker,
+    // The final statuses of the latest transactions that left the mempool.
+    final_tx_statuses: FinalTransactionStatuses,
+    // Broadcasts the changes in the mempool's transactions to its subscribers.
+    events: broadcast::Sender<MempoolEvent>,
+    metrics: MempoolMetrics,
+    clock: Arc<dyn Clock>,
+}
+
+impl Default for Mempool {
+    fn default() -> Self {
+        Mempool::with_config(MempoolConfig::default())
+    }
 }
 
 impl Mempool {
@@ -37,46 +84,314 @@ impl Mempool {
         Mempool::default()
     }
 
+    pub fn with_config(config: MempoolConfig) -> Self {
+        Mempool::with_config_and_clock(config, Arc::new(InstantClock))
+    }
+
+    pub fn with_config_and_clock(config: MempoolConfig, clock: Arc<dyn Clock>) -> Self {
+        Mempool {
+            tx_pool: TransactionPool::new(clock.clone()),
+            tx_queue: TransactionQueue::new(config.tx_ordering_policy, config.fairness_config),
+            account_nonces: HashMap::default(),
+            staged_txs: HashMap::default(),
+            account_fees: AccountFeeTracker::default(),
+            final_tx_statuses: FinalTransactionStatuses::new(config.max_txs),
+            events: broadcast::Sender::new(EVENT_CHANNEL_CAPACITY),
+            metrics: MempoolMetrics::default(),
+            config,
+            clock,
+        }
+    }
+
+    /// Restores a mempool from the given snapshot, reinserting its transactions by their arrival
+    /// order, and keeping their arrival sequence numbers. Transactions that are rejected by the
+    /// given configuration (e.g., due to a reduced capacity) are dropped.
+    /// Insertion times are not persisted, so the TTLs of the restored transactions restart from
+    /// the restoration.
+    pub fn restore(config: MempoolConfig, snapshot: MempoolSnapshot) -> Self {
+        let mut mempool = Mempool::with_config(config);
+        let MempoolSnapshot { mut txs, accounts } = snapshot;
+        let accounts: HashMap<ContractAddress, Account> =
+            accounts.into_iter().map(|account| (account.sender_address, account)).collect();
+
+        txs.sort_by_key(|tx| tx.arrival);
+        for SnapshotTransaction { tx, arrival } in txs {
+            let account = accounts.get(&tx.sender_address).copied().unwrap_or(Account {
+                sender_address: tx.sender_address,
+                state: AccountState { nonce: tx.nonce, balance: 0 },
+            });
+            let tx_hash = tx.tx_hash;
+            let input = MempoolInput { tx, account, depends_on_deploy_account: false };
+            if let Err(error) = mempool.insert_tx_with_arrival(input, Some(arrival)) {
+                warn!("Dropping transaction {tx_hash} while restoring the mempool: {error}");
+            }
+        }
+
+        mempool
+    }
+
+    /// Returns a snapshot of the transactions held in the mempool, ordered by their arrival, from
+    /// which it can be restored. Transactions handed out for sequencing are not included.
+    /// Accounts whose next nonce is unknown (i.e., whose transactions are held behind a nonce
+    /// gap) are recorded with the nonce preceding their first transaction, so that their
+    /// transactions are held again once restored.
+    pub fn snapshot(&self) -> MempoolSnapshot {
+        let txs: Vec<SnapshotTransaction> = self
+            .tx_pool
+            .txs_by_arrival()
+            .into_iter()
+            .map(|(tx, arrival)| SnapshotTransaction { tx: tx.clone(), arrival })
+            .collect();
+        let pool_accounts = txs.iter().map(|snapshot_tx| snapshot_tx.tx.sender_address);
+        let staged_accounts = self.staged_txs.values().map(|tx| tx.sender_address);
+        let account_addresses: HashSet<ContractAddress> =
+            pool_accounts.chain(staged_accounts).collect();
+
+        let accounts = account_addresses
+            .into_iter()
+            .filter_map(|sender_address| {
+                let nonce = self.known_account_nonce(sender_address).or_else(|| {
+                    let Nonce(first_nonce) = self.tx_pool.get_first_tx(sender_address)?.nonce;
+                    (first_nonce != StarkHash::ZERO).then(|| Nonce(first_nonce - StarkHash::ONE))
+                })?;
+                let state =
+                    AccountState { nonce, balance: self.account_fees.balance(sender_address) };
+                Some(Account { sender_address, state })
+            })
+            .collect();
+
+        MempoolSnapshot { txs, accounts }
+    }
+
+    /// Checks the consistency of the mempool's internal data structures: the pool's indices, the
+    /// queue's indices, and that the queue holds exactly the transactions with their accounts'
+    /// next nonces.
+    pub fn check_invariants(&self) -> InvariantReport {
+        let mut report = InvariantReport::default();
+        self.tx_pool.check_invariants(&mut report);
+        self.tx_queue.check_invariants(&mut report);
+
+        for tx in self.tx_queue.iter() {
+            if !self.tx_pool.contains(tx.tx_hash) {
+                report
+                    .report(InvariantViolation::QueuedTransactionNotInPool { tx_hash: tx.tx_hash });
+            }
+
+            let account_nonce = self.expected_queued_nonce(tx.sender_address);
+            if account_nonce != Some(tx.nonce) {
+                report.report(InvariantViolation::QueuedNonceMismatch {
+                    tx_hash: tx.tx_hash,
+                    queued_nonce: tx.nonce,
+                    account_nonce,
+                });
+            }
+        }
+
+        // The eligibility of transactions of accounts without staged or committed transactions is
+        // determined by the account states provided along with them, which are not kept.
+        for tx in self.tx_pool.iter_by_insertion_time() {
+            let is_eligible = self.account_nonces.get(&tx.sender_address) == Some(&tx.nonce);
+            if is_eligible && self.tx_queue.get_nonce(tx.sender_address) != Some(tx.nonce) {
+                report.report(InvariantViolation::EligibleTransactionNotQueued {
+                    tx_hash: tx.tx_hash,
+                });
+            }
+        }
+
+        for &tx_hash in self.staged_txs.keys() {
+            if self.tx_pool.contains(tx_hash) {
+                report.report(InvariantViolation::StagedTransactionInPool { tx_hash });
+            }
+        }
+
+        report
+    }
+
+    /// Checks the mempool's invariants if enabled by the configuration, logging any violation.
+    pub fn check_invariants_if_enabled(&self) {
+        if !self.config.check_invariants {
+            return;
+        }
+
+        let report = self.check_invariants();
+        if !report.is_ok() {
+            error!("Mempool invariants are violated: {:?}", report.violations);
+        }
+    }
+
+    /// Returns the mempool's metrics, in the Prometheus text exposition format.
+    pub fn metrics(&self) -> String {
+        let mut encoder = PrometheusEncoder::default();
+        self.tx_pool.encode_metrics(&mut encoder);
+        self.tx_queue.encode_metrics(&mut encoder);
+        encoder.gauge(
+            "mempool_staged_txs",
+            "Number of transactions handed out for sequencing, and not yet committed.",
+            self.staged_txs.len().try_into().unwrap_or(u64::MAX),
+        );
+        self.metrics.encode(&mut encoder);
+
+        encoder.finish()
+    }
+
+    /// Returns a receiver of the changes in the mempool's transactions, starting from the time of
+    /// the call.
+    pub fn subscribe(&self) -> broadcast::Receiver<MempoolEvent> {
+        self.events.subscribe()
+    }
+
     /// Returns an iterator of the current eligible transactions for sequencing, ordered by their
     /// priority.
     pub fn iter(&self) -> impl Iterator<Item = &TransactionReference> {
         self.tx_queue.iter()
     }
 
-    /// Retrieves up to `n_txs` transactions with the highest priority from the mempool.
-    /// Transactions are guaranteed to be unique across calls until `commit_block` is invoked.
-    // TODO: the last part about commit_block is incorrect if we delete txs in get_txs and then push
-    // back. TODO: Consider renaming to `pop_txs` to be more consistent with the standard
-    // library.
+    /// Retrieves up to `n_txs` transactions with the highest priority from the mempool, after
+    /// removing expired transactions.
+    /// Whenever a transaction is retrieved, the next transaction of the same account (if present)
+    /// becomes eligible for sequencing, so that several transactions of an account can be
+    /// retrieved in a single call.
+    /// Transactions are guaranteed to be unique across calls, unless returned to the mempool using
+    /// `return_txs`.
+    /// In fairness mode, the number of transactions retrieved per account is limited.
+    // TODO: Consider renaming to `pop_txs` to be more consistent with the standard library.
     pub fn get_txs(&mut self, n_txs: usize) -> MempoolResult<Vec<ThinTransaction>> {
-        let mut eligible_txs: Vec<ThinTransaction> = Vec::with_capacity(n_txs);
-        for tx_hash in self.tx_queue.pop_chunk(n_txs) {
-            let tx = self.tx_pool.remove(tx_hash)?;
-            eligible_txs.push(tx);
-        }
+        self.evict_expired(self.clock.now())?;
+
+        let mut deferred_txs = Vec::new();
+        let result = self.pop_txs(n_txs, &mut deferred_txs);
+        self.requeue_deferred_txs(deferred_txs);
+        let eligible_txs = result?;
+
+        self.metrics.record_get_txs(eligible_txs.len());
+
+        Ok(eligible_txs)
+    }
+
+    /// Retrieves the transactions with the highest priority from the mempool, as long as their
+    /// resource bounds fit within the given budget, after removing expired transactions.
+    /// Transactions that do not fit the remaining budget are skipped (along with the following
+    /// transactions of their accounts), rather than ending the retrieval; the retrieval ends once
+    /// a bounded number of transactions were skipped, as the remaining budget is then unlikely to
+    /// fit further transactions. Skipped transactions remain queued.
+    /// In fairness mode, the number of transactions retrieved per account is limited.
+    pub fn get_txs_with_budget(
+        &mut self,
+        budget: ResourceBudget,
+    ) -> MempoolResult<Vec<ThinTransaction>> {
+        self.evict_expired(self.clock.now())?;
+
+        let mut deferred_txs = Vec::new();
+        let result = self.pop_txs_with_budget(budget, &mut deferred_txs);
+        self.requeue_deferred_txs(deferred_txs);
+        let eligible_txs = result?;
+
+        self.metrics.record_get_txs(eligible_txs.len());
 
         Ok(eligible_txs)
     }
 
     /// Adds a new transaction to the mempool.
-    /// TODO: support fee escalation and transactions with future nonces.
-    /// TODO: check Account nonce and balance.
+    /// A transaction with the same sender and nonce as an existing one replaces it, provided it
+    /// escalates the fees of the existing transaction by the configured percentage.
+    /// When the mempool is at capacity, the lowest-tip transactions are evicted to make room for
+    /// the new transaction, provided it outbids them.
+    /// Transactions with future nonces are held until the nonce gap is filled.
+    /// A transaction is rejected if its account's balance cannot cover its maximal fee along with
+    /// those of the account's other transactions held by the mempool.
+    /// A transaction depending on a deploy account transaction of its sender is rejected unless
+    /// the latter is pending; evicting a transaction evicts the following transactions of its
+    /// account, including the dependents of a deploy account transaction.
+    /// Expired transactions are removed before the transaction is added.
     pub fn add_tx(&mut self, input: MempoolInput) -> MempoolResult<()> {
-        self.insert_tx(input)
+        let result = self.evict_expired(self.clock.now()).and_then(|()| self.insert_tx(input));
+        self.metrics.record_add_tx(&result);
+
+        result
+    }
+
+    /// Returns transactions that were retrieved by `get_txs` but not included in a block (e.g.,
+    /// due to a reverted proposal) to the mempool. Each account is rewound to its lowest returned
+    /// nonce, which becomes eligible for sequencing again.
+    /// Only transactions that are staged (i.e., retrieved and not yet committed) can be returned,
+    /// along with the staged transactions following them in their accounts, which could not be
+    /// sequenced otherwise; otherwise, none of the transactions are returned. Since staged
+    /// transactions follow their accounts' committed nonces, accounts are never rewound beyond
+    /// them.
+    /// Returned transactions are reinserted regardless of the mempool's capacity, as they were
+    /// already accounted for when first added.
+    pub fn return_txs(&mut self, txs: Vec<ThinTransaction>) -> MempoolResult<()> {
+        let mut returned_tx_hashes = HashSet::with_capacity(txs.len());
+        for tx in &txs {
+            let tx_hash = tx.tx_hash;
+            if !self.staged_txs.contains_key(&tx_hash) {
+                return Err(MempoolError::TransactionNotFound { tx_hash });
+            }
+            if !returned_tx_hashes.insert(tx_hash) {
+                return Err(MempoolError::DuplicateTransaction { tx_hash });
+            }
+        }
+
+        let lowest_returned_nonces = lowest_nonces(txs.iter().map(TransactionReference::new));
+        let unreturned_staged_tx = self.staged_txs.values().find(|tx| {
+            !returned_tx_hashes.contains(&tx.tx_hash)
+                && lowest_returned_nonces
+                    .get(&tx.sender_address)
+                    .is_some_and(|&nonce| tx.nonce > nonce)
+        });
+        if let Some(tx) = unreturned_staged_tx {
+            return Err(MempoolError::StagedTransactionNotReturned { tx_hash: tx.tx_hash });
+        }
+
+        let mut returned_txs = Vec::with_capacity(txs.len());
+        for tx in txs {
+            if let Some(tx_reference) = self.staged_txs.remove(&tx.tx_hash) {
+                // Returned transactions keep their place in arrival-based orderings.
+                returned_txs.push(self.tx_pool.reinsert(tx, tx_reference.arrival)?);
+            }
+        }
+        for tx in &returned_txs {
+            self.emit(MempoolEvent::TransactionReturned(tx.tx_hash));
+        }
+
+        self.rewind_accounts(returned_txs);
+
+        Ok(())
     }
 
     /// Update the mempool's internal state according to the committed block (resolves nonce gaps,
     /// updates account balances).
-    // TODO: the part about resolving nonce gaps is incorrect if we delete txs in get_txs and then
-    // push back.
-    // state_changes: a map that associates each account address with the state of the committed
-    // block.
-    pub fn commit_block(
-        &mut self,
-        state_changes: HashMap<ContractAddress, AccountState>,
-    ) -> MempoolResult<()> {
-        for (address, AccountState { nonce }) in state_changes {
-            let next_nonce = nonce.try_increment().map_err(|_| MempoolError::FeltOutOfRange)?;
+    /// Accounts of rejected transactions are rewound to the lowest rejected nonce, as their nonce
+    /// was not advanced; their staged transactions following that nonce can no longer be
+    /// sequenced, and are discarded. The dependents of a rejected deploy account transaction are
+    /// evicted as well. Rejected transactions that are not staged are ignored.
+    /// Staged transactions whose nonces were committed can no longer be sequenced, and are
+    /// discarded even if not reported as included. Accounts left without transactions in the
+    /// mempool are forgotten.
+    pub fn commit_block(&mut self, args: CommitBlockArgs) -> MempoolResult<()> {
+        let CommitBlockArgs { state_changes, included_tx_hashes, rejected_tx_hashes } = args;
+        let committed_nonces: HashMap<ContractAddress, Nonce> =
+            state_changes.iter().map(|(&address, state)| (address, state.nonce)).collect();
+
+        for &tx_hash in &included_tx_hashes {
+            self.staged_txs.remove(&tx_hash);
+            self.account_fees.remove(tx_hash);
+            self.final_tx_statuses.insert(tx_hash, TransactionStatus::Included);
+            self.emit(MempoolEvent::TransactionIncluded(tx_hash));
+        }
+
+        for (address, AccountState { nonce, balance }) in state_changes {
+            self.account_fees.set_balance(address, balance);
+            let committed_next_nonce =
+                nonce.try_increment().map_err(|_| MempoolError::FeltOutOfRange)?;
+
+            // Transactions handed out for sequencing beyond the committed nonce are not yet
+            // committed; keep the account aligned with them.
+            let next_nonce =
+                self.account_nonces.get(&address).map_or(committed_next_nonce, |&account_nonce| {
+                    account_nonce.max(committed_next_nonce)
+                });
+            self.account_nonces.insert(address, next_nonce);
 
             // Align the queue with the committed nonces.
             if self
@@ -88,45 +403,635 @@ impl Mempool {
             }
 
             if self.tx_queue.get_nonce(address).is_none() {
-                if let Some(tx) = self.tx_pool.get_by_address_and_nonce(address, nonce) {
+                if let Some(tx) = self.tx_pool.get_by_address_and_nonce(address, next_nonce) {
                     self.tx_queue.insert(*tx);
                 }
             }
 
-            self.tx_pool.remove_up_to_nonce(address, next_nonce);
+            // Transactions whose nonce was taken by other transactions can no longer be
+            // sequenced.
+            for tx_hash in self.tx_pool.remove_up_to_nonce(address, next_nonce) {
+                self.account_fees.remove(tx_hash);
+                self.final_tx_statuses.insert(tx_hash, TransactionStatus::Evicted);
+                self.emit(MempoolEvent::TransactionEvicted(tx_hash));
+            }
+        }
+
+        let committed_staged_tx_hashes: Vec<TransactionHash> = self
+            .staged_txs
+            .values()
+            .filter(|tx| {
+                committed_nonces.get(&tx.sender_address).is_some_and(|&nonce| tx.nonce <= nonce)
+            })
+            .map(|tx| tx.tx_hash)
+            .collect();
+        for tx_hash in committed_staged_tx_hashes {
+            self.discard_staged_tx(tx_hash);
         }
+
+        let rejected_txs: Vec<TransactionReference> = rejected_tx_hashes
+            .iter()
+            .filter_map(|tx_hash| self.staged_txs.remove(tx_hash))
+            .collect();
+        for tx in &rejected_txs {
+            self.account_fees.remove(tx.tx_hash);
+            self.final_tx_statuses.insert(tx.tx_hash, TransactionStatus::Rejected);
+            self.emit(MempoolEvent::TransactionRejected(tx.tx_hash));
+
+            // The transactions following a rejected deploy account transaction depend on it.
+            if tx.is_deploy_account {
+                let dependent_tx_hashes =
+                    self.tx_pool.account_tx_hashes_from_nonce(tx.sender_address, tx.nonce);
+                if let Some(&first_dependent_tx_hash) = dependent_tx_hashes.first() {
+                    self.evict_tx(first_dependent_tx_hash)?;
+                }
+            }
+        }
+        self.rewind_accounts(rejected_txs);
+        self.prune_accounts();
+
+        Ok(())
+    }
+
+    /// Reverts the latest committed block: its transactions are reinserted into the mempool, and
+    /// the given accounts are restored to the given nonces (i.e., the nonces of their next
+    /// transactions, as of the state preceding the block). Each affected account's transaction
+    /// with the restored nonce becomes eligible for sequencing.
+    /// Transactions with a nonce lower than their account's restored nonce can no longer be
+    /// sequenced, and are discarded. A reverted transaction whose sender and nonce are taken by a
+    /// transaction already held in the mempool is discarded as well, and so are the affected
+    /// accounts' staged transactions, which were handed out for sequencing on top of the reverted
+    /// block.
+    /// Several blocks are reverted by calling this method for each of them, latest first.
+    /// Reverted transactions are reinserted regardless of the mempool's capacity.
+    pub fn revert_block(
+        &mut self,
+        txs: Vec<ThinTransaction>,
+        restored_nonces: HashMap<ContractAddress, Nonce>,
+    ) -> MempoolResult<()> {
+        // Accounts without a restored nonce are rewound to their lowest reverted nonce.
+        let mut account_nonces = lowest_nonces(txs.iter().map(TransactionReference::new));
+        account_nonces.extend(restored_nonces);
+
+        for tx in txs {
+            let ThinTransaction { sender_address, nonce, tx_hash, .. } = tx;
+            self.staged_txs.remove(&tx_hash);
+            if self.tx_pool.contains(tx_hash) {
+                continue;
+            }
+
+            let is_nonce_taken =
+                self.tx_pool.get_by_address_and_nonce(sender_address, nonce).is_some();
+            if is_nonce_taken || nonce < account_nonces[&sender_address] {
+                self.final_tx_statuses.insert(tx_hash, TransactionStatus::Evicted);
+                self.emit(MempoolEvent::TransactionEvicted(tx_hash));
+                continue;
+            }
+
+            let tx_fee = max_fee(&tx);
+            self.tx_pool.insert(tx)?;
+            self.account_fees.insert(tx_hash, sender_address, tx_fee);
+            self.emit(MempoolEvent::TransactionReturned(tx_hash));
+        }
+
+        for (address, nonce) in account_nonces {
+            self.discard_staged_txs_from_nonce(address, nonce);
+            self.account_nonces.insert(address, nonce);
+
+            for tx_hash in self.tx_pool.remove_up_to_nonce(address, nonce) {
+                self.account_fees.remove(tx_hash);
+                self.final_tx_statuses.insert(tx_hash, TransactionStatus::Evicted);
+                self.emit(MempoolEvent::TransactionEvicted(tx_hash));
+            }
+
+            self.tx_queue.remove(address);
+            if let Some(tx) = self.tx_pool.get_by_address_and_nonce(address, nonce) {
+                self.tx_queue.insert(*tx);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the status of the given transaction.
+    /// The statuses of transactions that left the mempool are kept for a limited number of
+    /// transactions (the mempool's capacity).
+    pub fn get_tx_status(&self, tx_hash: TransactionHash) -> MempoolResult<TransactionStatus> {
+        if self.staged_txs.contains_key(&tx_hash) {
+            return Ok(TransactionStatus::Staged);
+        }
+
+        if self.tx_pool.contains(tx_hash) {
+            let ThinTransaction { sender_address, nonce, .. } =
+                *self.tx_pool.get_by_tx_hash(tx_hash)?;
+            return Ok(if self.tx_queue.get_nonce(sender_address) == Some(nonce) {
+                TransactionStatus::Queued
+            } else {
+                TransactionStatus::Pending
+            });
+        }
+
+        self.final_tx_statuses.get(tx_hash).ok_or(MempoolError::TransactionNotFound { tx_hash })
+    }
+
+    /// Removes the transactions that were inserted at least the configured TTL before `now`
+    /// from the mempool, along with the following transactions of their accounts, which can no
+    /// longer be sequenced.
+    pub fn evict_expired(&mut self, now: Instant) -> MempoolResult<()> {
+        let Some(expiration_time) = now.checked_sub(self.config.tx_ttl) else {
+            return Ok(());
+        };
+
+        for tx_hash in self.tx_pool.get_inserted_until(expiration_time) {
+            // The transaction may have been evicted along with a preceding transaction of its
+            // account.
+            if self.tx_pool.contains(tx_hash) {
+                self.evict_tx(tx_hash)?;
+            }
+        }
+
         Ok(())
     }
 
     fn insert_tx(&mut self, input: MempoolInput) -> MempoolResult<()> {
-        let MempoolInput { tx, account } = input;
-        let tx_reference = TransactionReference::new(&tx);
+        self.insert_tx_with_arrival(input, None)
+    }
+
+    /// Inserts the given transaction, keeping its arrival sequence number if given (e.g., for a
+    /// restored transaction), and otherwise assigning it the next one.
+    fn insert_tx_with_arrival(
+        &mut self,
+        input: MempoolInput,
+        arrival: Option<u64>,
+    ) -> MempoolResult<()> {
+        let MempoolInput { tx, account, depends_on_deploy_account } = input;
+
+        // Checked ahead of the replacement and eviction of other transactions, which must only
+        // take place once the transaction is certain to be inserted.
+        if self.tx_pool.contains(tx.tx_hash) {
+            return Err(MempoolError::DuplicateTransaction { tx_hash: tx.tx_hash });
+        }
+
+        let account_nonce = self.account_nonce(account);
+        if tx.nonce < account_nonce {
+            return Err(MempoolError::NonceTooOld {
+                tx_hash: tx.tx_hash,
+                tx_nonce: tx.nonce,
+                account_nonce,
+            });
+        }
+
+        if depends_on_deploy_account
+            && !self.is_deploy_account_pending(tx.sender_address, account_nonce)
+        {
+            return Err(MempoolError::MissingDeployAccount { tx_hash: tx.tx_hash });
+        }
 
-        self.tx_pool.insert(tx)?;
+        self.check_account_limits(&tx, account)?;
 
-        if is_eligible_for_sequencing(tx_reference, account) {
+        let tx_fee = max_fee(&tx);
+        let replaced_tx_hash = self
+            .tx_pool
+            .get_by_address_and_nonce(tx.sender_address, tx.nonce)
+            .map(|replaced_tx| replaced_tx.tx_hash);
+        if !self.account_fees.can_afford(
+            tx.sender_address,
+            account.state.balance,
+            tx_fee,
+            replaced_tx_hash,
+        ) {
+            return Err(MempoolError::InsufficientBalance { tx_hash: tx.tx_hash });
+        }
+
+        if let Some(replaced_tx_hash) = replaced_tx_hash {
+            self.validate_fee_escalation(replaced_tx_hash, &tx)?;
+        }
+        let evicted_txs = self.select_txs_to_evict(&tx)?;
+
+        // The transaction is validated; other transactions are only removed from this point on.
+        let replaced_queued_tx = match replaced_tx_hash {
+            Some(replaced_tx_hash) => self.replace_tx(replaced_tx_hash, &tx)?,
+            None => false,
+        };
+        for tx_hash in evicted_txs {
+            self.evict_tx(tx_hash)?;
+        }
+
+        let tx_reference = match arrival {
+            Some(arrival) => self.tx_pool.reinsert(tx.clone(), arrival)?,
+            None => self.tx_pool.insert(tx.clone())?,
+        };
+        // The balance is only updated once the transaction is accepted.
+        self.account_fees.set_balance(account.sender_address, account.state.balance);
+        self.account_fees.insert(tx_reference.tx_hash, tx_reference.sender_address, tx_fee);
+
+        let is_eligible_for_sequencing = tx_reference.nonce == account_nonce
+            && self.tx_queue.get_nonce(tx_reference.sender_address).is_none();
+        if replaced_queued_tx || is_eligible_for_sequencing {
             self.tx_queue.insert(tx_reference);
         }
+        self.emit(MempoolEvent::TransactionAdded(tx));
 
         Ok(())
     }
 
+    /// Verifies that the incoming transaction does not exceed the configured limits of its
+    /// account: the nonce distance ahead of the account's nonce, and the number of transactions
+    /// held in the mempool (a replacement does not add to the latter).
+    fn check_account_limits(&self, tx: &ThinTransaction, account: Account) -> MempoolResult<()> {
+        let ThinTransaction { sender_address, nonce, tx_hash, .. } = *tx;
+        let MempoolConfig { max_txs_per_account, max_nonce_distance, .. } = self.config;
+
+        let account_nonce = account.state.nonce;
+        if nonce > Nonce(account_nonce.0 + max_nonce_distance) {
+            return Err(MempoolError::NonceTooFarAhead { tx_hash, tx_nonce: nonce, account_nonce });
+        }
+
+        let is_replacement = self.tx_pool.get_by_address_and_nonce(sender_address, nonce).is_some();
+        if !is_replacement && self.tx_pool.account_txs_count(sender_address) >= max_txs_per_account
+        {
+            return Err(MempoolError::TooManyAccountTransactions { tx_hash, max_txs_per_account });
+        }
+
+        Ok(())
+    }
+
+    /// Checks whether the deploy account transaction of the given account (with the given next
+    /// nonce) is pending, i.e., held in the pool or already handed out for sequencing.
+    fn is_deploy_account_pending(&self, address: ContractAddress, account_nonce: Nonce) -> bool {
+        if account_nonce > Nonce::default() {
+            return true;
+        }
+
+        self.tx_pool
+            .get_by_address_and_nonce(address, Nonce::default())
+            .is_some_and(|tx| tx.is_deploy_account)
+    }
+
+    /// Returns the nonce of the given account's queued transaction, as derived from the mempool's
+    /// transactions: the account's next nonce if it has staged or committed transactions, and
+    /// otherwise the nonce of its first transaction in the pool.
+    fn expected_queued_nonce(&self, address: ContractAddress) -> Option<Nonce> {
+        self.account_nonces
+            .get(&address)
+            .copied()
+            .or_else(|| self.tx_pool.get_first_tx(address).map(|tx| tx.nonce))
+    }
+
+    /// Returns the next nonce of the given account, considering its state, the transactions
+    /// already handed out for sequencing, and its queued transaction (whose nonce is the
+    /// account's next one).
+    fn account_nonce(&self, account: Account) -> Nonce {
+        let Account { sender_address, state: AccountState { nonce, .. } } = account;
+        self.known_account_nonce(sender_address)
+            .map_or(nonce, |account_nonce| account_nonce.max(nonce))
+    }
+
+    /// Returns the next nonce of the given account, if known to the mempool: recorded for
+    /// accounts with staged or committed transactions, and otherwise that of the account's queued
+    /// transaction.
+    fn known_account_nonce(&self, address: ContractAddress) -> Option<Nonce> {
+        self.account_nonces.get(&address).copied().or_else(|| self.tx_queue.get_nonce(address))
+    }
+
+    /// Rewinds the account of each of the given transactions to the lowest of its nonces, making
+    /// the transaction with that nonce (if present in the pool) the account's eligible one.
+    /// The account's staged transactions from that nonce on can no longer be sequenced, and are
+    /// discarded.
+    fn rewind_accounts(&mut self, txs: impl IntoIterator<Item = TransactionReference>) {
+        for (address, nonce) in lowest_nonces(txs) {
+            self.discard_staged_txs_from_nonce(address, nonce);
+            self.account_nonces.insert(address, nonce);
+            self.tx_queue.remove(address);
+            if let Some(tx) = self.tx_pool.get_by_address_and_nonce(address, nonce) {
+                self.tx_queue.insert(*tx);
+            }
+        }
+    }
+
+    /// Discards the staged transactions of the given account with a nonce at least the given one.
+    fn discard_staged_txs_from_nonce(&mut self, address: ContractAddress, nonce: Nonce) {
+        let discarded_tx_hashes: Vec<TransactionHash> = self
+            .staged_txs
+            .values()
+            .filter(|tx| tx.sender_address == address && tx.nonce >= nonce)
+            .map(|tx| tx.tx_hash)
+            .collect();
+        for tx_hash in discarded_tx_hashes {
+            self.discard_staged_tx(tx_hash);
+        }
+    }
+
+    /// Discards the given staged transaction, which can no longer be sequenced; as it was handed
+    /// out, it cannot be reinserted into the pool.
+    fn discard_staged_tx(&mut self, tx_hash: TransactionHash) {
+        self.staged_txs.remove(&tx_hash);
+        self.account_fees.remove(tx_hash);
+        self.final_tx_statuses.insert(tx_hash, TransactionStatus::Evicted);
+        self.emit(MempoolEvent::TransactionEvicted(tx_hash));
+    }
+
+    /// Forgets the nonces and balances of accounts without transactions in the mempool (held or
+    /// staged); they are provided again along with the accounts' next transactions.
+    fn prune_accounts(&mut self) {
+        let staged_accounts: HashSet<ContractAddress> =
+            self.staged_txs.values().map(|tx| tx.sender_address).collect();
+        let has_txs = |address: &ContractAddress| {
+            staged_accounts.contains(address) || self.tx_pool.account_txs_count(*address) > 0
+        };
+        self.account_nonces.retain(|address, _| has_txs(address));
+        self.account_fees.retain_balances(has_txs);
+    }
+
+    /// Hands out up to `n_txs` queued transactions for sequencing, by priority. Queued
+    /// transactions of accounts that reached their limit are moved to `deferred_txs`.
+    fn pop_txs(
+        &mut self,
+        n_txs: usize,
+        deferred_txs: &mut Vec<TransactionReference>,
+    ) -> MempoolResult<Vec<ThinTransaction>> {
+        let mut eligible_txs: Vec<ThinTransaction> = Vec::with_capacity(n_txs);
+        let mut n_remaining_txs = n_txs;
+        let mut account_tx_counts = HashMap::new();
+
+        while n_remaining_txs > 0 && !self.tx_queue.is_empty() {
+            let chunk =
+                self.tx_queue.pop_chunk(n_remaining_txs, &mut account_tx_counts, deferred_txs);
+            n_remaining_txs -= chunk.len();
+
+            for tx_reference in chunk {
+                eligible_txs.push(self.stage_tx(tx_reference)?);
+            }
+        }
+
+        Ok(eligible_txs)
+    }
+
+    /// Hands out queued transactions for sequencing, by priority, as long as they fit within the
+    /// given budget. Queued transactions that are skipped (i.e., do not fit the remaining budget,
+    /// or their accounts reached their limit) are moved to `deferred_txs`.
+    fn pop_txs_with_budget(
+        &mut self,
+        budget: ResourceBudget,
+        deferred_txs: &mut Vec<TransactionReference>,
+    ) -> MempoolResult<Vec<ThinTransaction>> {
+        let max_txs_per_account = self
+            .config
+            .fairness_config
+            .map_or(usize::MAX, |fairness_config| fairness_config.max_txs_per_account);
+        let mut remaining_budget = budget;
+        let mut eligible_txs: Vec<ThinTransaction> = Vec::new();
+        let mut account_tx_counts: HashMap<ContractAddress, usize> = HashMap::new();
+        let mut n_skipped_txs = 0;
+
+        while n_skipped_txs < MAX_SKIPPED_TXS_PER_RETRIEVAL {
+            let Some(&tx_reference) = self.tx_queue.iter().next() else {
+                break;
+            };
+            let sender_address = tx_reference.sender_address;
+            self.tx_queue.remove(sender_address);
+
+            let account_tx_count = account_tx_counts.entry(sender_address).or_default();
+            if *account_tx_count >= max_txs_per_account {
+                deferred_txs.push(tx_reference);
+                continue;
+            }
+
+            let (Some(l1_gas), Some(l2_gas)) = (
+                remaining_budget.l1_gas.checked_sub(tx_reference.l1_gas.max_amount),
+                remaining_budget.l2_gas.checked_sub(tx_reference.l2_gas.max_amount),
+            ) else {
+                deferred_txs.push(tx_reference);
+                n_skipped_txs += 1;
+                continue;
+            };
+
+            remaining_budget = ResourceBudget { l1_gas, l2_gas };
+            *account_tx_count += 1;
+            eligible_txs.push(self.stage_tx(tx_reference)?);
+        }
+
+        Ok(eligible_txs)
+    }
+
+    /// Requeues the transactions deferred during a retrieval.
+    fn requeue_deferred_txs(&mut self, deferred_txs: Vec<TransactionReference>) {
+        for tx in deferred_txs {
+            self.tx_queue.insert(tx);
+        }
+    }
+
+    /// Hands out the given queued transaction for sequencing, and enqueues the next transaction of
+    /// its account.
+    fn stage_tx(&mut self, tx_reference: TransactionReference) -> MempoolResult<ThinTransaction> {
+        let tx = self.tx_pool.remove(tx_reference.tx_hash)?;
+        self.staged_txs.insert(tx_reference.tx_hash, tx_reference);
+        self.enqueue_next_eligible_tx(tx_reference)?;
+        self.emit(MempoolEvent::TransactionStaged(tx_reference.tx_hash));
+
+        Ok(tx)
+    }
+
+    /// Advances the account of the given (retrieved) transaction, and enqueues its next
+    /// transaction, if present in the pool.
+    fn enqueue_next_eligible_tx(&mut self, tx: TransactionReference) -> MempoolResult<()> {
+        let next_nonce = tx.nonce.try_increment().map_err(|_| MempoolError::FeltOutOfRange)?;
+        self.account_nonces.insert(tx.sender_address, next_nonce);
+
+        if let Some(next_tx) = self.tx_pool.get_by_address_and_nonce(tx.sender_address, next_nonce)
+        {
+            self.tx_queue.insert(*next_tx);
+        }
+
+        Ok(())
+    }
+
+    /// Verifies that the incoming transaction sufficiently escalates the fees of the existing
+    /// transaction with the same sender and nonce, which it replaces.
+    fn validate_fee_escalation(
+        &self,
+        existing_tx_hash: TransactionHash,
+        incoming_tx: &ThinTransaction,
+    ) -> MempoolResult<()> {
+        let existing_tx = self.tx_pool.get_by_tx_hash(existing_tx_hash)?;
+        if !is_fee_escalated(existing_tx, incoming_tx, self.config.fee_escalation_percentage) {
+            return Err(MempoolError::InsufficientFeeEscalation {
+                tx_hash: incoming_tx.tx_hash,
+                existing_tx_hash,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Removes the existing transaction replaced by the incoming transaction (with the same sender
+    /// and nonce). Returns whether the removed transaction was queued.
+    fn replace_tx(
+        &mut self,
+        existing_tx_hash: TransactionHash,
+        incoming_tx: &ThinTransaction,
+    ) -> MempoolResult<bool> {
+        let ThinTransaction { sender_address, nonce, tx_hash, .. } = *incoming_tx;
+        self.tx_pool.remove(existing_tx_hash)?;
+        self.account_fees.remove(existing_tx_hash);
+        self.final_tx_statuses.insert(existing_tx_hash, TransactionStatus::Evicted);
+        self.emit(MempoolEvent::TransactionReplaced {
+            replaced_tx_hash: existing_tx_hash,
+            tx_hash,
+        });
+        let is_queued = self.tx_queue.get_nonce(sender_address) == Some(nonce);
+        if is_queued {
+            self.tx_queue.remove(sender_address);
+        }
+
+        Ok(is_queued)
+    }
+
+    /// Selects the transactions to evict so that the incoming transaction fits within the
+    /// mempool's capacity, lowest tip first. Only the highest-nonce transaction of an account is a
+    /// candidate at any point, so that eviction does not create nonce gaps, and transactions of
+    /// the incoming transaction's sender are never selected.
+    /// Fails if the incoming transaction does not outbid all the transactions it would evict.
+    fn select_txs_to_evict(
+        &self,
+        incoming_tx: &ThinTransaction,
+    ) -> MempoolResult<Vec<TransactionHash>> {
+        let ThinTransaction { sender_address, nonce, tx_hash, tip, .. } = *incoming_tx;
+        let MempoolConfig { max_txs, max_size_in_bytes, .. } = self.config;
+
+        let mut n_txs = self.tx_pool.len();
+        let mut size_in_bytes = self.tx_pool.size_in_bytes();
+        // A replaced transaction frees its place.
+        if let Some(replaced_tx) = self.tx_pool.get_by_address_and_nonce(sender_address, nonce) {
+            n_txs -= 1;
+            size_in_bytes -= estimated_tx_size(self.tx_pool.get_by_tx_hash(replaced_tx.tx_hash)?);
+        }
+        n_txs += 1;
+        size_in_bytes += estimated_tx_size(incoming_tx);
+
+        let mut evicted_txs = Vec::new();
+        if n_txs <= max_txs && size_in_bytes <= max_size_in_bytes {
+            return Ok(evicted_txs);
+        }
+
+        let eviction_key =
+            |tx: &TransactionReference| Reverse((tx.tip, tx.tx_hash, tx.sender_address, tx.nonce));
+        let mut candidates: BinaryHeap<_> = self
+            .tx_pool
+            .last_txs_by_account()
+            .filter(|tx| tx.sender_address != sender_address)
+            .map(eviction_key)
+            .collect();
+
+        while n_txs > max_txs || size_in_bytes > max_size_in_bytes {
+            let Some(Reverse((
+                candidate_tip,
+                candidate_tx_hash,
+                candidate_address,
+                candidate_nonce,
+            ))) = candidates.pop()
+            else {
+                return Err(MempoolError::MempoolFull { tx_hash });
+            };
+            if candidate_tip >= tip {
+                return Err(MempoolError::MempoolFull { tx_hash });
+            }
+
+            n_txs -= 1;
+            size_in_bytes -= estimated_tx_size(self.tx_pool.get_by_tx_hash(candidate_tx_hash)?);
+            evicted_txs.push(candidate_tx_hash);
+
+            if let Some(preceding_tx) =
+                self.tx_pool.get_preceding_tx(candidate_address, candidate_nonce)
+            {
+                candidates.push(eviction_key(preceding_tx));
+            }
+        }
+
+        Ok(evicted_txs)
+    }
+
+    /// Evicts the given transaction, along with the following transactions of its account (e.g.,
+    /// the dependents of a deploy account transaction), which can no longer be sequenced.
+    fn evict_tx(&mut self, tx_hash: TransactionHash) -> MempoolResult<()> {
+        let ThinTransaction { sender_address, nonce, .. } =
+            *self.tx_pool.get_by_tx_hash(tx_hash)?;
+        for evicted_tx_hash in self.tx_pool.account_tx_hashes_from_nonce(sender_address, nonce) {
+            let ThinTransaction { nonce, .. } = self.tx_pool.remove(evicted_tx_hash)?;
+            self.account_fees.remove(evicted_tx_hash);
+            self.final_tx_statuses.insert(evicted_tx_hash, TransactionStatus::Evicted);
+            self.emit(MempoolEvent::TransactionEvicted(evicted_tx_hash));
+            if self.tx_queue.get_nonce(sender_address) == Some(nonce) {
+                self.tx_queue.remove(sender_address);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Sends the given event to the mempool's subscribers, if there are any.
+    fn emit(&self, event: MempoolEvent) {
+        // Sending only fails when there are no subscribers.
+        let _ = self.events.send(event);
+    }
+
     #[cfg(test)]
     pub(crate) fn _tx_pool(&self) -> &TransactionPool {
         &self.tx_pool
     }
+
+    #[cfg(test)]
+    pub(crate) fn _account_nonces(&self) -> &HashMap<ContractAddress, Nonce> {
+        &self.account_nonces
+    }
+}
+
+/// Holds the final statuses of the latest transactions that left the mempool, up to a fixed
+/// capacity; the oldest records are dropped first.
+#[derive(Debug, Default)]
+struct FinalTransactionStatuses {
+    statuses: HashMap<TransactionHash, TransactionStatus>,
+    insertion_order: VecDeque<TransactionHash>,
+    capacity: usize,
+}
+
+impl FinalTransactionStatuses {
+    fn new(capacity: usize) -> Self {
+        FinalTransactionStatuses { capacity, ..Default::default() }
+    }
+
+    fn insert(&mut self, tx_hash: TransactionHash, status: TransactionStatus) {
+        if self.statuses.insert(tx_hash, status).is_none() {
+            self.insertion_order.push_back(tx_hash);
+        }
+
+        while self.insertion_order.len() > self.capacity {
+            if let Some(oldest_tx_hash) = self.insertion_order.pop_front() {
+                self.statuses.remove(&oldest_tx_hash);
+            }
+        }
+    }
+
+    fn get(&self, tx_hash: TransactionHash) -> Option<TransactionStatus> {
+        self.statuses.get(&tx_hash).copied()
+    }
 }
 
 /// Provides a lightweight representation of a transaction for mempool usage (e.g., excluding
 /// execution fields).
 /// TODO(Mohammad): rename this struct to `ThinTransaction` once that name
 /// becomes available, to better reflect its purpose and usage.
-#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Default)]
 pub struct TransactionReference {
     pub sender_address: ContractAddress,
     pub nonce: Nonce,
     pub tx_hash: TransactionHash,
     pub tip: Tip,
+    pub l1_gas: ResourceBounds,
+    pub l2_gas: ResourceBounds,
+    pub is_deploy_account: bool,
+    // The sequence number of the transaction's arrival to the mempool, assigned by the pool.
+    pub arrival: u64,
 }
 
 impl TransactionReference {
@@ -136,10 +1041,76 @@ impl TransactionReference {
             nonce: tx.nonce,
             tx_hash: tx.tx_hash,
             tip: tx.tip,
+            l1_gas: tx.resource_bounds.l1_gas,
+            l2_gas: tx.resource_bounds.l2_gas,
+            is_deploy_account: tx.is_deploy_account(),
+            arrival: 0,
         }
     }
 }
 
-fn is_eligible_for_sequencing(tx_reference: TransactionReference, account: Account) -> bool {
-    tx_reference.nonce == account.state.nonce
+// Note: the arrival sequence is not compared, as it depends on the mempool the transaction arrived
+// to.
+impl PartialEq for TransactionReference {
+    fn eq(&self, other: &Self) -> bool {
+        let TransactionReference {
+            sender_address,
+            nonce,
+            tx_hash,
+            tip,
+            l1_gas,
+            l2_gas,
+            is_deploy_account,
+            ..
+        } = *other;
+        self.sender_address == sender_address
+            && self.nonce == nonce
+            && self.tx_hash == tx_hash
+            && self.tip == tip
+            && self.l1_gas == l1_gas
+            && self.l2_gas == l2_gas
+            && self.is_deploy_account == is_deploy_account
+    }
+}
+
+impl Eq for TransactionReference {}
+
+/// Returns the lowest nonce of each account among the given transactions.
+fn lowest_nonces(
+    txs: impl IntoIterator<Item = TransactionReference>,
+) -> HashMap<ContractAddress, Nonce> {
+    let mut account_nonces: HashMap<ContractAddress, Nonce> = HashMap::new();
+    for tx in txs {
+        account_nonces
+            .entry(tx.sender_address)
+            .and_modify(|account_nonce| *account_nonce = (*account_nonce).min(tx.nonce))
+            .or_insert(tx.nonce);
+    }
+
+    account_nonces
+}
+
+/// Checks whether the incoming transaction raises the tip and every resource bound price of the
+/// existing transaction by at least the given percentage, and strictly raises at least one of
+/// them.
+fn is_fee_escalated(
+    existing_tx: &ThinTransaction,
+    incoming_tx: &ThinTransaction,
+    escalation_percentage: u8,
+) -> bool {
+    let fees = |tx: &ThinTransaction| {
+        [
+            u128::from(tx.tip.0),
+            tx.resource_bounds.l1_gas.max_price_per_unit,
+            tx.resource_bounds.l2_gas.max_price_per_unit,
+        ]
+    };
+    let (existing_fees, incoming_fees) = (fees(existing_tx), fees(incoming_tx));
+
+    let is_escalated = zip(existing_fees, incoming_fees).all(|(existing_fee, incoming_fee)| {
+        let escalation = existing_fee.saturating_mul(escalation_percentage.into()) / 100;
+        incoming_fee >= existing_fee.saturating_add(escalation)
+    });
+
+    is_escalated && incoming_fees != existing_fees
 }
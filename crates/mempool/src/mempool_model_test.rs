@@ -0,0 +1,326 @@
+//! Property-based tests, driving the mempool with arbitrary sequences of operations and comparing
+//! it against a simple reference model. Failing sequences are shrunk to a minimal reproduction.
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use proptest::collection::vec;
+use proptest::option;
+use proptest::prelude::*;
+use proptest::test_runner::TestCaseError;
+use starknet_api::core::{ContractAddress, Nonce};
+use starknet_api::hash::StarkHash;
+use starknet_api::transaction::{Tip, TransactionHash};
+use starknet_mempool_types::errors::MempoolError;
+use starknet_mempool_types::mempool_types::{
+    Account, AccountState, CommitBlockArgs, MempoolInput, ThinTransaction, TransactionStatus,
+};
+use starknet_types_core::felt::Felt;
+
+use crate::config::MempoolConfig;
+use crate::mempool::mempool_test::executable_invoke_tx;
+use crate::mempool::Mempool;
+
+const N_CASES: u32 = 256;
+const MAX_OPERATIONS: usize = 64;
+const N_ACCOUNTS: u8 = 3;
+const MAX_NONCE: u8 = 6;
+const MAX_TIP: u64 = 20;
+const MAX_CHUNK_SIZE: usize = 5;
+
+#[derive(Clone, Copy, Debug)]
+enum Operation {
+    AddTx { account: u8, nonce: u8, tip: u64 },
+    GetTxs { n_txs: usize },
+    // Returns up to `n_txs` of the transactions of the given account retrieved since the previous
+    // commit, highest nonce first (as the following transactions must be returned along with
+    // them).
+    ReturnTxs { account: u8, n_txs: usize },
+    // Commits the transactions retrieved since the previous commit; those of the rejected account
+    // (if any) are rejected. Unless all of them are reported, only the first one is, as the
+    // following ones are neither included nor rejected.
+    CommitBlock { rejected_account: Option<u8>, report_all_rejected: bool },
+}
+
+/// Generates operations; the earlier variants (and smaller values) are the simpler ones, towards
+/// which failing operations are shrunk.
+fn operation() -> impl Strategy<Value = Operation> {
+    prop_oneof![
+        6 => (0..N_ACCOUNTS, 0..MAX_NONCE, 0..MAX_TIP)
+            .prop_map(|(account, nonce, tip)| Operation::AddTx { account, nonce, tip }),
+        3 => (0..=MAX_CHUNK_SIZE).prop_map(|n_txs| Operation::GetTxs { n_txs }),
+        1 => (0..N_ACCOUNTS, 1..=MAX_CHUNK_SIZE)
+            .prop_map(|(account, n_txs)| Operation::ReturnTxs { account, n_txs }),
+        1 => (option::of(0..N_ACCOUNTS), any::<bool>()).prop_map(
+            |(rejected_account, report_all_rejected)| Operation::CommitBlock {
+                rejected_account,
+                report_all_rejected,
+            }
+        ),
+    ]
+}
+
+fn address(account: u8) -> ContractAddress {
+    ContractAddress::from(u128::from(account))
+}
+
+/// A straightforward reference implementation of the mempool's expected behavior.
+#[derive(Default)]
+struct MempoolModel {
+    // The transactions held in the mempool, by account and nonce.
+    txs: BTreeMap<(ContractAddress, Nonce), ThinTransaction>,
+    // The committed nonce of each account.
+    committed_nonces: HashMap<ContractAddress, Nonce>,
+    // The next nonce of each account, considering the retrieved transactions.
+    next_nonces: HashMap<ContractAddress, Nonce>,
+    // Transactions retrieved since the previous commit.
+    retrieved_txs: Vec<ThinTransaction>,
+}
+
+impl MempoolModel {
+    fn committed_nonce(&self, address: ContractAddress) -> Nonce {
+        self.committed_nonces.get(&address).copied().unwrap_or_default()
+    }
+
+    fn next_nonce(&self, address: ContractAddress) -> Nonce {
+        self.next_nonces.get(&address).copied().unwrap_or_default()
+    }
+
+    fn add_tx(&mut self, tx: ThinTransaction) -> Result<(), &'static str> {
+        let key = (tx.sender_address, tx.nonce);
+        if tx.nonce < self.next_nonce(tx.sender_address) {
+            return Err("NonceTooOld");
+        }
+
+        if let Some(existing_tx) = self.txs.get(&key) {
+            let existing_tip = existing_tx.tip.0;
+            let min_tip = existing_tip + existing_tip * 10 / 100;
+            if tx.tip.0 < min_tip || tx.tip.0 == existing_tip {
+                return Err("InsufficientFeeEscalation");
+            }
+        }
+
+        self.txs.insert(key, tx);
+        Ok(())
+    }
+
+    /// Retrieves transactions in rounds: each round retrieves the transactions with the highest
+    /// tip (then hash) among the transactions with their account's next nonce, after which the
+    /// following transactions of their accounts become eligible for the next round.
+    fn get_txs(&mut self, n_txs: usize) -> Vec<ThinTransaction> {
+        let mut txs = Vec::new();
+        loop {
+            let mut eligible_keys: Vec<(ContractAddress, Nonce)> = self
+                .txs
+                .iter()
+                .filter(|((address, nonce), _)| *nonce == self.next_nonce(*address))
+                .map(|(&key, _)| key)
+                .collect();
+            if eligible_keys.is_empty() || txs.len() == n_txs {
+                break;
+            }
+
+            eligible_keys.sort_by_key(|key| {
+                let tx = &self.txs[key];
+                Reverse((tx.tip, tx.tx_hash))
+            });
+            eligible_keys.truncate(n_txs - txs.len());
+            for key in eligible_keys {
+                let tx = self.txs.remove(&key).expect("Eligible transaction should exist.");
+                self.next_nonces.insert(tx.sender_address, tx.nonce.try_increment().unwrap());
+                txs.push(tx);
+            }
+        }
+
+        self.retrieved_txs.extend(txs.iter().cloned());
+        txs
+    }
+
+    /// Returns up to `n_txs` of the retrieved transactions of the given account to the mempool,
+    /// highest nonce first, rewinding the account to the lowest returned nonce.
+    fn return_txs(&mut self, address: ContractAddress, n_txs: usize) -> Vec<ThinTransaction> {
+        let mut account_nonces: Vec<Nonce> = self
+            .retrieved_txs
+            .iter()
+            .filter(|tx| tx.sender_address == address)
+            .map(|tx| tx.nonce)
+            .collect();
+        account_nonces.sort_by_key(|&nonce| Reverse(nonce));
+        let Some(lowest_returned_nonce) = account_nonces.into_iter().take(n_txs).min() else {
+            return Vec::new();
+        };
+
+        let (returned_txs, retrieved_txs) = std::mem::take(&mut self.retrieved_txs)
+            .into_iter()
+            .partition(|tx| tx.sender_address == address && tx.nonce >= lowest_returned_nonce);
+        self.retrieved_txs = retrieved_txs;
+
+        self.rewind(address, &returned_txs);
+        for tx in &returned_txs {
+            self.txs.insert((tx.sender_address, tx.nonce), tx.clone());
+        }
+
+        returned_txs
+    }
+
+    /// Commits the retrieved transactions, except for those of the rejected account, which are
+    /// discarded; the rejected account is rewound to the lowest rejected nonce.
+    fn commit_block(
+        &mut self,
+        rejected_address: Option<ContractAddress>,
+        report_all_rejected: bool,
+    ) -> CommitBlockArgs {
+        let (rejected_txs, included_txs): (Vec<_>, Vec<_>) =
+            std::mem::take(&mut self.retrieved_txs)
+                .into_iter()
+                .partition(|tx| Some(tx.sender_address) == rejected_address);
+
+        let mut args = CommitBlockArgs::default();
+        for tx in included_txs {
+            args.included_tx_hashes.insert(tx.tx_hash);
+            let state = args.state_changes.entry(tx.sender_address).or_default();
+            state.nonce = state.nonce.max(tx.nonce);
+            let committed_nonce = tx.nonce.try_increment().unwrap();
+            let account_committed_nonce =
+                self.committed_nonces.entry(tx.sender_address).or_default();
+            *account_committed_nonce = (*account_committed_nonce).max(committed_nonce);
+        }
+
+        if let Some(rejected_address) = rejected_address {
+            self.rewind(rejected_address, &rejected_txs);
+        }
+        let mut rejected_txs: Vec<&ThinTransaction> = rejected_txs.iter().collect();
+        rejected_txs.sort_by_key(|tx| tx.nonce);
+        if !report_all_rejected {
+            rejected_txs.truncate(1);
+        }
+        args.rejected_tx_hashes = rejected_txs.iter().map(|tx| tx.tx_hash).collect();
+
+        args
+    }
+
+    fn rewind(&mut self, address: ContractAddress, txs: &[ThinTransaction]) {
+        if let Some(lowest_nonce) = txs.iter().map(|tx| tx.nonce).min() {
+            self.next_nonces.insert(address, lowest_nonce);
+        }
+    }
+}
+
+fn error_name(error: &MempoolError) -> &'static str {
+    match error {
+        MempoolError::NonceTooOld { .. } => "NonceTooOld",
+        MempoolError::InsufficientFeeEscalation { .. } => "InsufficientFeeEscalation",
+        _ => "Unexpected",
+    }
+}
+
+/// Runs the given operations on both the mempool and the model, and checks that they agree.
+fn run(operations: &[Operation]) -> Result<(), TestCaseError> {
+    let mut mempool = Mempool::with_config(MempoolConfig::default());
+    let mut model = MempoolModel::default();
+    let mut retrieved_tx_hashes: HashSet<TransactionHash> = HashSet::new();
+    let mut n_added_txs: u64 = 0;
+
+    for (i, &operation) in operations.iter().enumerate() {
+        match operation {
+            Operation::AddTx { account, nonce, tip } => {
+                n_added_txs += 1;
+                let sender_address = address(account);
+                let (tip, nonce) = (Tip(tip), Nonce(Felt::from(nonce)));
+                let tx = ThinTransaction {
+                    sender_address,
+                    tx_hash: TransactionHash(StarkHash::from(n_added_txs)),
+                    tip,
+                    nonce,
+                    resource_bounds: Default::default(),
+                    executable_tx: executable_invoke_tx(
+                        sender_address,
+                        nonce,
+                        tip,
+                        Default::default(),
+                        0,
+                    ),
+                };
+                let account = Account {
+                    sender_address,
+                    state: AccountState {
+                        nonce: model.committed_nonce(sender_address),
+                        ..Default::default()
+                    },
+                };
+
+                let expected_result = model.add_tx(tx.clone());
+                let result =
+                    mempool.add_tx(MempoolInput { tx, account, depends_on_deploy_account: false });
+                prop_assert_eq!(
+                    result.as_ref().map_err(error_name).copied(),
+                    expected_result,
+                    "Operation {}: {:?}",
+                    i,
+                    result
+                );
+            }
+            Operation::GetTxs { n_txs } => {
+                let expected_txs = model.get_txs(n_txs);
+                let txs = mempool.get_txs(n_txs)?;
+                prop_assert_eq!(&txs, &expected_txs, "Operation {}", i);
+
+                for tx in txs {
+                    prop_assert!(
+                        retrieved_tx_hashes.insert(tx.tx_hash),
+                        "Operation {}: transaction {} was retrieved twice.",
+                        i,
+                        tx.tx_hash
+                    );
+                }
+            }
+            Operation::ReturnTxs { account, n_txs } => {
+                let returned_txs = model.return_txs(address(account), n_txs);
+                for tx in &returned_txs {
+                    retrieved_tx_hashes.remove(&tx.tx_hash);
+                }
+                mempool.return_txs(returned_txs)?;
+            }
+            Operation::CommitBlock { rejected_account, report_all_rejected } => {
+                let staged_tx_hashes: Vec<TransactionHash> =
+                    model.retrieved_txs.iter().map(|tx| tx.tx_hash).collect();
+                let args = model.commit_block(rejected_account.map(address), report_all_rejected);
+                mempool.commit_block(args)?;
+
+                // No transaction remains staged once its block is committed.
+                for tx_hash in staged_tx_hashes {
+                    prop_assert_ne!(
+                        mempool.get_tx_status(tx_hash),
+                        Ok(TransactionStatus::Staged),
+                        "Operation {}: transaction {} remains staged.",
+                        i,
+                        tx_hash
+                    );
+                }
+            }
+        }
+
+        let report = mempool.check_invariants();
+        prop_assert!(report.is_ok(), "Operation {}: invariants are violated: {:?}.", i, report);
+
+        // No transaction is lost: the mempool holds exactly the model's transactions.
+        let mut tx_hashes: Vec<TransactionHash> =
+            mempool._tx_pool()._tx_pool().keys().copied().collect();
+        tx_hashes.sort();
+        let mut expected_tx_hashes: Vec<TransactionHash> =
+            model.txs.values().map(|tx| tx.tx_hash).collect();
+        expected_tx_hashes.sort();
+        prop_assert_eq!(tx_hashes, expected_tx_hashes, "Operation {}", i);
+    }
+
+    Ok(())
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(N_CASES))]
+
+    #[test]
+    fn test_mempool_matches_model(operations in vec(operation(), 1..=MAX_OPERATIONS)) {
+        run(&operations)?;
+    }
+}
@@ -1,21 +1,36 @@
 use std::cmp::Reverse;
 use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use assert_matches::assert_matches;
 use itertools::{enumerate, zip_eq};
 use pretty_assertions::assert_eq;
 use rstest::{fixture, rstest};
 use starknet_api::core::{ContractAddress, Nonce, PatriciaKey};
+use starknet_api::data_availability::DataAvailabilityMode;
 use starknet_api::hash::StarkHash;
-use starknet_api::transaction::{Tip, TransactionHash};
+use starknet_api::rpc_transaction::{
+    RPCDeployAccountTransaction, RPCDeployAccountTransactionV3, RPCInvokeTransaction,
+    RPCInvokeTransactionV3, RPCTransaction, ResourceBoundsMapping,
+};
+use starknet_api::transaction::{Calldata, ResourceBounds, Tip, TransactionHash};
 use starknet_api::{contract_address, felt, patricia_key};
 use starknet_mempool_types::errors::MempoolError;
-use starknet_mempool_types::mempool_types::{Account, AccountState, ThinTransaction};
+use starknet_mempool_types::mempool_types::{
+    Account, AccountState, CommitBlockArgs, ExecutableTransaction, MempoolEvent, ResourceBudget,
+    ThinTransaction, TransactionStatus,
+};
 use starknet_types_core::felt::Felt;
 
-use crate::mempool::{Mempool, MempoolInput, TransactionReference};
-use crate::transaction_pool::TransactionPool;
+use crate::config::{FairnessConfig, MempoolConfig, MempoolSnapshotConfig};
+use crate::invariants::{InvariantReport, InvariantViolation};
+use crate::mempool::{Mempool, MempoolInput, TransactionReference, MAX_SKIPPED_TXS_PER_RETRIEVAL};
+use crate::ordering::TransactionOrderingPolicy;
+use crate::snapshot::{write_snapshots, MempoolSnapshot, SnapshotTransaction};
+use crate::transaction_pool::{estimated_tx_size, TransactionPool};
 use crate::transaction_queue::TransactionQueue;
+use crate::utils::Clock;
 
 /// Represents the internal state of the mempool.
 /// Enables customized (and potentially inconsistent) creation for unit testing.
@@ -44,7 +59,7 @@ impl MempoolState {
 impl From<MempoolState> for Mempool {
     fn from(mempool_state: MempoolState) -> Mempool {
         let MempoolState { tx_pool, tx_queue } = mempool_state;
-        Mempool { tx_pool, tx_queue }
+        Mempool { tx_pool, tx_queue, ..Default::default() }
     }
 }
 
@@ -68,9 +83,95 @@ impl FromIterator<TransactionReference> for TransactionQueue {
     }
 }
 
+/// A clock whose time only changes when advanced explicitly.
+#[derive(Debug)]
+struct FakeClock {
+    now: Mutex<Instant>,
+}
+
+impl FakeClock {
+    fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+}
+
+impl Default for FakeClock {
+    fn default() -> Self {
+        FakeClock { now: Mutex::new(Instant::now()) }
+    }
+}
+
+impl Clock for FakeClock {
+    fn now(&self) -> Instant {
+        *self.now.lock().unwrap()
+    }
+}
+
 #[track_caller]
 fn add_tx(mempool: &mut Mempool, input: &MempoolInput) {
     assert_eq!(mempool.add_tx(input.clone()), Ok(()));
+    assert_eq!(mempool.check_invariants(), InvariantReport::default());
+}
+
+/// Turns the transaction of the given input into a deploy account transaction.
+fn into_deploy_account_input(mut input: MempoolInput) -> MempoolInput {
+    let deploy_account_tx = RPCDeployAccountTransactionV3 {
+        signature: Default::default(),
+        nonce: input.tx.nonce,
+        class_hash: Default::default(),
+        contract_address_salt: Default::default(),
+        constructor_calldata: Default::default(),
+        resource_bounds: input.tx.resource_bounds.clone(),
+        tip: input.tx.tip,
+        paymaster_data: Default::default(),
+        nonce_data_availability_mode: DataAvailabilityMode::L1,
+        fee_data_availability_mode: DataAvailabilityMode::L1,
+    };
+    input.tx.executable_tx = Arc::new(ExecutableTransaction {
+        tx: RPCTransaction::DeployAccount(RPCDeployAccountTransaction::V3(deploy_account_tx)),
+        compiled_class_info: None,
+    });
+    input
+}
+
+/// Returns an executable invoke transaction with the given fields, and the given number of
+/// calldata felts.
+pub fn executable_invoke_tx(
+    sender_address: ContractAddress,
+    nonce: Nonce,
+    tip: Tip,
+    resource_bounds: ResourceBoundsMapping,
+    calldata_length: usize,
+) -> Arc<ExecutableTransaction> {
+    let invoke_tx = RPCInvokeTransactionV3 {
+        sender_address,
+        calldata: Calldata(Arc::new(vec![Felt::ZERO; calldata_length])),
+        signature: Default::default(),
+        nonce,
+        resource_bounds,
+        tip,
+        paymaster_data: Default::default(),
+        account_deployment_data: Default::default(),
+        nonce_data_availability_mode: DataAvailabilityMode::L1,
+        fee_data_availability_mode: DataAvailabilityMode::L1,
+    };
+    Arc::new(ExecutableTransaction {
+        tx: RPCTransaction::Invoke(RPCInvokeTransaction::V3(invoke_tx)),
+        compiled_class_info: None,
+    })
+}
+
+/// Turns the transaction of the given input into an invoke transaction with the given number of
+/// calldata felts.
+fn into_invoke_input(mut input: MempoolInput, calldata_length: usize) -> MempoolInput {
+    input.tx.executable_tx = executable_invoke_tx(
+        input.tx.sender_address,
+        input.tx.nonce,
+        input.tx.tip,
+        input.tx.resource_bounds.clone(),
+        calldata_length,
+    );
+    input
 }
 
 /// Creates a valid input for mempool's `add_tx` with optional default values.
@@ -85,14 +186,18 @@ macro_rules! add_tx_input {
         tx_nonce: $tx_nonce:expr, account_nonce: $account_nonce:expr) => {{
         let sender_address = contract_address!($sender_address);
         let account_nonce = Nonce(felt!($account_nonce));
-        let account = Account { sender_address, state: AccountState {nonce: account_nonce}};
+        let account = Account { sender_address, state: AccountState { nonce: account_nonce, ..Default::default() }};
+        let tip = Tip($tip);
+        let nonce = Nonce(felt!($tx_nonce));
         let tx = ThinTransaction {
-            tip: Tip($tip),
+            tip,
             tx_hash: TransactionHash(StarkHash::from($tx_hash)),
             sender_address,
-            nonce: Nonce(felt!($tx_nonce)),
+            nonce,
+            resource_bounds: Default::default(),
+            executable_tx: executable_invoke_tx(sender_address, nonce, tip, Default::default(), 0),
         };
-        MempoolInput { tx, account }
+        MempoolInput { tx, account, depends_on_deploy_account: false }
     }};
     (tx_hash: $tx_hash:expr, sender_address: $sender_address:expr, tx_nonce: $tx_nonce:expr, account_nonce: $account_nonce:expr) => {
         add_tx_input!(tip: 0, tx_hash: $tx_hash, sender_address: $sender_address, tx_nonce: $tx_nonce, account_nonce: $account_nonce)
@@ -108,6 +213,12 @@ macro_rules! add_tx_input {
     };
 }
 
+/// Sets the maximal fee of the transaction and the balance of its account.
+fn set_max_fee_and_balance(input: &mut MempoolInput, max_fee: u128, balance: u128) {
+    input.tx.resource_bounds.l1_gas = ResourceBounds { max_amount: 1, max_price_per_unit: max_fee };
+    input.account.state.balance = balance;
+}
+
 #[fixture]
 fn mempool() -> Mempool {
     Mempool::empty()
@@ -175,8 +286,6 @@ fn test_get_txs(#[case] requested_txs: usize) {
 }
 
 #[rstest]
-// TODO(Ayelet): remove ignore once replenishing is merged.
-#[ignore]
 fn test_get_txs_multi_nonce() {
     // Setup.
     let tx_address_0_nonce_0 =
@@ -197,6 +306,95 @@ fn test_get_txs_multi_nonce() {
     expected_mempool_state.assert_eq_mempool_state(&mempool);
 }
 
+#[rstest]
+fn test_get_txs_with_budget_skips_txs_exceeding_budget(mut mempool: Mempool) {
+    // Setup.
+    let mut input_tip_30_l1_gas_60 = add_tx_input!(tip: 30, tx_hash: 1, sender_address: "0x0");
+    input_tip_30_l1_gas_60.tx.resource_bounds.l1_gas.max_amount = 60;
+    let mut input_tip_20_l1_gas_50 = add_tx_input!(tip: 20, tx_hash: 2, sender_address: "0x1");
+    input_tip_20_l1_gas_50.tx.resource_bounds.l1_gas.max_amount = 50;
+    let mut input_tip_10_l1_gas_40 = add_tx_input!(tip: 10, tx_hash: 3, sender_address: "0x2");
+    input_tip_10_l1_gas_40.tx.resource_bounds.l1_gas.max_amount = 40;
+    let mut input_tip_5_l2_gas_10 = add_tx_input!(tip: 5, tx_hash: 4, sender_address: "0x3");
+    input_tip_5_l2_gas_10.tx.resource_bounds.l2_gas.max_amount = 10;
+
+    for input in [
+        &input_tip_30_l1_gas_60,
+        &input_tip_20_l1_gas_50,
+        &input_tip_10_l1_gas_40,
+        &input_tip_5_l2_gas_10,
+    ] {
+        add_tx(&mut mempool, input);
+    }
+
+    // Test.
+    let txs = mempool.get_txs_with_budget(ResourceBudget { l1_gas: 100, l2_gas: 5 }).unwrap();
+
+    // Assert: transactions that do not fit are skipped, and kept in the mempool.
+    assert_eq!(txs, &[input_tip_30_l1_gas_60.tx, input_tip_10_l1_gas_40.tx]);
+    let expected_queue_txs = [
+        TransactionReference::new(&input_tip_5_l2_gas_10.tx),
+        TransactionReference::new(&input_tip_20_l1_gas_50.tx),
+    ];
+    let expected_pool_txs = [input_tip_20_l1_gas_50.tx, input_tip_5_l2_gas_10.tx];
+    let expected_mempool_state = MempoolState::new(expected_pool_txs, expected_queue_txs);
+    expected_mempool_state.assert_eq_mempool_state(&mempool);
+}
+
+#[rstest]
+fn test_get_txs_with_budget_ends_after_skipped_txs(mut mempool: Mempool) {
+    // Setup: the highest-priority transactions exceed the budget.
+    let exceeding_inputs: Vec<MempoolInput> = (0..MAX_SKIPPED_TXS_PER_RETRIEVAL)
+        .map(|i| {
+            let i = u64::try_from(i).unwrap();
+            let mut input = add_tx_input!(tip: 100 + i, tx_hash: i + 1, sender_address: i + 1);
+            input.tx.resource_bounds.l1_gas.max_amount = 100;
+            input
+        })
+        .collect();
+    let fitting_input = add_tx_input!(tip: 1, tx_hash: 1000, sender_address: "0x0");
+
+    for input in exceeding_inputs.iter().chain([&fitting_input]) {
+        add_tx(&mut mempool, input);
+    }
+
+    // Test.
+    let txs = mempool.get_txs_with_budget(ResourceBudget { l1_gas: 10, l2_gas: 0 }).unwrap();
+
+    // Assert: the retrieval ended before reaching the fitting transaction, and all transactions
+    // remain queued.
+    assert_eq!(txs, []);
+    assert_eq!(mempool.iter().count(), MAX_SKIPPED_TXS_PER_RETRIEVAL + 1);
+    assert_eq!(mempool.check_invariants(), InvariantReport::default());
+}
+
+#[rstest]
+fn test_get_txs_with_budget_multi_nonce(mut mempool: Mempool) {
+    // Setup.
+    let mut input_nonce_0 =
+        add_tx_input!(tx_hash: 1, sender_address: "0x0", tx_nonce: 0_u8, account_nonce: 0_u8);
+    input_nonce_0.tx.resource_bounds.l1_gas.max_amount = 10;
+    let mut input_nonce_1 =
+        add_tx_input!(tx_hash: 2, sender_address: "0x0", tx_nonce: 1_u8, account_nonce: 0_u8);
+    input_nonce_1.tx.resource_bounds.l1_gas.max_amount = 10;
+    let mut input_nonce_2 =
+        add_tx_input!(tx_hash: 3, sender_address: "0x0", tx_nonce: 2_u8, account_nonce: 0_u8);
+    input_nonce_2.tx.resource_bounds.l1_gas.max_amount = 10;
+
+    for input in [&input_nonce_0, &input_nonce_1, &input_nonce_2] {
+        add_tx(&mut mempool, input);
+    }
+
+    // Test.
+    let txs = mempool.get_txs_with_budget(ResourceBudget { l1_gas: 25, l2_gas: 0 }).unwrap();
+
+    // Assert: the account's transactions are retrieved in nonce order, until the budget ends.
+    assert_eq!(txs, &[input_nonce_0.tx, input_nonce_1.tx]);
+    let expected_queue_txs = [TransactionReference::new(&input_nonce_2.tx)];
+    let expected_mempool_state = MempoolState::new([input_nonce_2.tx], expected_queue_txs);
+    expected_mempool_state.assert_eq_mempool_state(&mempool);
+}
+
 #[rstest]
 fn test_add_tx(mut mempool: Mempool) {
     // Setup.
@@ -272,6 +470,32 @@ fn test_add_tx_with_duplicate_tx(mut mempool: Mempool) {
     assert_eq_mempool_queue(&mempool, &[same_input.tx])
 }
 
+#[rstest]
+fn test_add_tx_with_duplicate_tx_hash_does_not_replace_tx(mut mempool: Mempool) {
+    // Setup.
+    let input_nonce_0 = add_tx_input!(tip: 1, tx_hash: 1, sender_address: "0x0", tx_nonce: 0_u8, account_nonce: 0_u8);
+    let input_nonce_1 = add_tx_input!(tip: 1, tx_hash: 2, sender_address: "0x0", tx_nonce: 1_u8, account_nonce: 0_u8);
+    // Escalates the fees of the transaction with nonce 1, but reuses the hash of the first one.
+    let duplicate_input = add_tx_input!(tip: 100, tx_hash: 1, sender_address: "0x0", tx_nonce: 1_u8, account_nonce: 0_u8);
+    for input in [&input_nonce_0, &input_nonce_1] {
+        add_tx(&mut mempool, input);
+    }
+
+    // Test.
+    assert_matches!(
+        mempool.add_tx(duplicate_input),
+        Err(MempoolError::DuplicateTransaction { .. })
+    );
+
+    // Assert: the transaction with nonce 1 was not replaced.
+    assert_eq_mempool_state(
+        &mempool,
+        &[input_nonce_0.tx.clone(), input_nonce_1.tx],
+        &[input_nonce_0.tx],
+    );
+    assert_eq!(mempool.check_invariants(), InvariantReport::default());
+}
+
 #[rstest]
 fn test_add_tx_with_identical_tip_succeeds(mut mempool: Mempool) {
     // Setup.
@@ -309,6 +533,47 @@ fn test_tip_priority_over_tx_hash(mut mempool: Mempool) {
     assert_eq_mempool_queue(&mempool, &[input_big_tip_small_hash.tx, input_small_tip_big_hash.tx])
 }
 
+#[rstest]
+#[case::tip(TransactionOrderingPolicy::Tip, [1, 2, 3])]
+#[case::effective_l2_gas_price(TransactionOrderingPolicy::EffectiveL2GasPrice, [2, 3, 1])]
+#[case::tip_per_gas(TransactionOrderingPolicy::TipPerGas, [3, 2, 1])]
+#[case::fifo(TransactionOrderingPolicy::Fifo, [2, 1, 3])]
+fn test_tx_ordering_policy(
+    #[case] tx_ordering_policy: TransactionOrderingPolicy,
+    #[case] expected_tx_hashes: [u8; 3],
+) {
+    // Setup.
+    let mut mempool =
+        Mempool::with_config(MempoolConfig { tx_ordering_policy, ..Default::default() });
+
+    let input = |tip, tx_hash: u8, sender_address: &str, l2_gas_price, l2_gas_amount| {
+        let mut input = add_tx_input!(tip: tip, tx_hash: tx_hash, sender_address: sender_address);
+        input.tx.resource_bounds.l2_gas =
+            ResourceBounds { max_amount: l2_gas_amount, max_price_per_unit: l2_gas_price };
+        input.account.state.balance = u128::MAX;
+        input
+    };
+    let input_tip_10_l2_price_1_tip_per_gas_low = input(10, 1, "0x0", 1, 100);
+    let input_tip_5_l2_price_30_tip_per_gas_mid = input(5, 2, "0x1", 30, 10);
+    let input_tip_1_l2_price_20_tip_per_gas_high = input(1, 3, "0x2", 20, 1);
+
+    // Test.
+    for input in [
+        &input_tip_5_l2_price_30_tip_per_gas_mid,
+        &input_tip_10_l2_price_1_tip_per_gas_low,
+        &input_tip_1_l2_price_20_tip_per_gas_high,
+    ] {
+        add_tx(&mut mempool, input);
+    }
+
+    // Assert.
+    let txs = mempool.get_txs(3).unwrap();
+    let tx_hashes: Vec<_> = txs.iter().map(|tx| tx.tx_hash).collect();
+    let expected_tx_hashes: Vec<_> =
+        expected_tx_hashes.map(|tx_hash| TransactionHash(StarkHash::from(tx_hash))).to_vec();
+    assert_eq!(tx_hashes, expected_tx_hashes);
+}
+
 #[rstest]
 fn test_get_txs_with_holes_multiple_accounts() {
     // Setup.
@@ -417,9 +682,1500 @@ fn test_flow_filling_holes(mut mempool: Mempool) {
     add_tx(&mut mempool, &input_address_0_nonce_0);
     let txs = mempool.get_txs(2).unwrap();
 
-    // TODO(Ayelet): all transactions should be returned after replenishing.
     // Assert: all remaining transactions are returned.
+    assert_eq!(txs, &[input_address_0_nonce_0.tx, input_address_0_nonce_1.tx]);
+}
+
+#[rstest]
+fn test_get_txs_replenishes_queue_with_next_nonce(mut mempool: Mempool) {
+    // Setup.
+    let input_address_0_nonce_0 = add_tx_input!(tip: 30, tx_hash: 1, sender_address: "0x0",
+        tx_nonce: 0_u8, account_nonce: 0_u8);
+    let input_address_0_nonce_1 = add_tx_input!(tip: 30, tx_hash: 2, sender_address: "0x0",
+        tx_nonce: 1_u8, account_nonce: 0_u8);
+    let input_address_1_nonce_0 = add_tx_input!(tip: 20, tx_hash: 3, sender_address: "0x1",
+        tx_nonce: 0_u8, account_nonce: 0_u8);
+
+    for input in [&input_address_0_nonce_0, &input_address_0_nonce_1, &input_address_1_nonce_0] {
+        add_tx(&mut mempool, input);
+    }
+
+    // Test.
+    let txs = mempool.get_txs(1).unwrap();
+
+    // Assert: the account's next transaction was enqueued in place of the retrieved one.
     assert_eq!(txs, &[input_address_0_nonce_0.tx]);
+    let expected_queue_txs = [
+        TransactionReference::new(&input_address_0_nonce_1.tx),
+        TransactionReference::new(&input_address_1_nonce_0.tx),
+    ];
+    let expected_pool_txs = [input_address_0_nonce_1.tx, input_address_1_nonce_0.tx];
+    let expected_mempool_state = MempoolState::new(expected_pool_txs, expected_queue_txs);
+    expected_mempool_state.assert_eq_mempool_state(&mempool);
+}
+
+#[rstest]
+fn test_get_txs_stops_at_nonce_gap(mut mempool: Mempool) {
+    // Setup.
+    let input_nonce_0 =
+        add_tx_input!(tx_hash: 1, sender_address: "0x0", tx_nonce: 0_u8, account_nonce: 0_u8);
+    let input_nonce_2 =
+        add_tx_input!(tx_hash: 2, sender_address: "0x0", tx_nonce: 2_u8, account_nonce: 0_u8);
+
+    add_tx(&mut mempool, &input_nonce_0);
+    add_tx(&mut mempool, &input_nonce_2);
+
+    // Test.
+    let txs = mempool.get_txs(2).unwrap();
+
+    // Assert: the future-nonce transaction is held in the pool.
+    assert_eq!(txs, &[input_nonce_0.tx]);
+    let expected_mempool_state = MempoolState::new([input_nonce_2.tx], []);
+    expected_mempool_state.assert_eq_mempool_state(&mempool);
+}
+
+#[rstest]
+fn test_add_tx_after_get_txs_continues_account_nonce(mut mempool: Mempool) {
+    // Setup.
+    let input_nonce_0 =
+        add_tx_input!(tx_hash: 1, sender_address: "0x0", tx_nonce: 0_u8, account_nonce: 0_u8);
+    let input_nonce_1 =
+        add_tx_input!(tx_hash: 2, sender_address: "0x0", tx_nonce: 1_u8, account_nonce: 0_u8);
+
+    add_tx(&mut mempool, &input_nonce_0);
+    mempool.get_txs(1).unwrap();
+
+    // Test.
+    add_tx(&mut mempool, &input_nonce_1);
+
+    // Assert: the transaction is eligible, although the account state is not yet committed.
+    let expected_queue_txs = [TransactionReference::new(&input_nonce_1.tx)];
+    let expected_mempool_state = MempoolState::new([input_nonce_1.tx], expected_queue_txs);
+    expected_mempool_state.assert_eq_mempool_state(&mempool);
+}
+
+#[rstest]
+fn test_add_tx_with_old_nonce_after_get_txs(mut mempool: Mempool) {
+    // Setup.
+    let input =
+        add_tx_input!(tx_hash: 1, sender_address: "0x0", tx_nonce: 0_u8, account_nonce: 0_u8);
+    let input_same_nonce =
+        add_tx_input!(tx_hash: 2, sender_address: "0x0", tx_nonce: 0_u8, account_nonce: 0_u8);
+
+    add_tx(&mut mempool, &input);
+    mempool.get_txs(1).unwrap();
+
+    // Test and assert.
+    assert_eq!(
+        mempool.add_tx(input_same_nonce),
+        Err(MempoolError::NonceTooOld {
+            tx_hash: TransactionHash(StarkHash::TWO),
+            tx_nonce: Nonce(felt!(0_u8)),
+            account_nonce: Nonce(felt!(1_u8)),
+        })
+    );
+}
+
+#[rstest]
+fn test_commit_block_enqueues_next_nonce(mut mempool: Mempool) {
+    // Setup.
+    let input_nonce_3 =
+        add_tx_input!(tx_hash: 1, sender_address: "0x0", tx_nonce: 3_u8, account_nonce: 0_u8);
+    add_tx(&mut mempool, &input_nonce_3);
+
+    // Test.
+    let state_changes = HashMap::from([(
+        contract_address!("0x0"),
+        AccountState { nonce: Nonce(felt!(2_u8)), ..Default::default() },
+    )]);
+    assert!(mempool.commit_block(CommitBlockArgs { state_changes, ..Default::default() }).is_ok());
+
+    // Assert: the transaction following the committed nonce became eligible.
+    let expected_queue_txs = [TransactionReference::new(&input_nonce_3.tx)];
+    let expected_mempool_state = MempoolState::new([input_nonce_3.tx], expected_queue_txs);
+    expected_mempool_state.assert_eq_mempool_state(&mempool);
+}
+
+#[rstest]
+fn test_commit_block_rewinds_account_of_rejected_tx(mut mempool: Mempool) {
+    // Setup.
+    let input_nonce_0 =
+        add_tx_input!(tx_hash: 1, sender_address: "0x0", tx_nonce: 0_u8, account_nonce: 0_u8);
+    let input_nonce_1 =
+        add_tx_input!(tx_hash: 2, sender_address: "0x0", tx_nonce: 1_u8, account_nonce: 0_u8);
+    let input_nonce_2 =
+        add_tx_input!(tx_hash: 3, sender_address: "0x0", tx_nonce: 2_u8, account_nonce: 0_u8);
+    let input_nonce_1_resubmitted =
+        add_tx_input!(tx_hash: 4, sender_address: "0x0", tx_nonce: 1_u8, account_nonce: 0_u8);
+
+    add_tx(&mut mempool, &input_nonce_0);
+    add_tx(&mut mempool, &input_nonce_1);
+    mempool.get_txs(2).unwrap();
+    add_tx(&mut mempool, &input_nonce_2);
+
+    // Test.
+    let args = CommitBlockArgs {
+        state_changes: HashMap::from([(
+            contract_address!("0x0"),
+            AccountState { nonce: Nonce(felt!(0_u8)), ..Default::default() },
+        )]),
+        included_tx_hashes: [input_nonce_0.tx.tx_hash].into(),
+        rejected_tx_hashes: [input_nonce_1.tx.tx_hash].into(),
+    };
+    assert!(mempool.commit_block(args).is_ok());
+
+    // Assert: the account's next transaction is no longer eligible.
+    let expected_mempool_state = MempoolState::new([input_nonce_2.tx.clone()], []);
+    expected_mempool_state.assert_eq_mempool_state(&mempool);
+
+    // Assert: the rejected nonce can be resubmitted.
+    add_tx(&mut mempool, &input_nonce_1_resubmitted);
+    let expected_queue_txs = [TransactionReference::new(&input_nonce_1_resubmitted.tx)];
+    let expected_pool_txs = [input_nonce_1_resubmitted.tx, input_nonce_2.tx];
+    let expected_mempool_state = MempoolState::new(expected_pool_txs, expected_queue_txs);
+    expected_mempool_state.assert_eq_mempool_state(&mempool);
+}
+
+#[rstest]
+fn test_commit_block_discards_staged_txs_following_rejected_tx(mut mempool: Mempool) {
+    // Setup.
+    let input_nonce_0 =
+        add_tx_input!(tx_hash: 1, sender_address: "0x0", tx_nonce: 0_u8, account_nonce: 0_u8);
+    let input_nonce_1 =
+        add_tx_input!(tx_hash: 2, sender_address: "0x0", tx_nonce: 1_u8, account_nonce: 0_u8);
+    let input_nonce_2 =
+        add_tx_input!(tx_hash: 3, sender_address: "0x0", tx_nonce: 2_u8, account_nonce: 0_u8);
+    let unknown_tx_hash = TransactionHash(felt!(4_u8));
+
+    for input in [&input_nonce_0, &input_nonce_1, &input_nonce_2] {
+        add_tx(&mut mempool, input);
+    }
+    mempool.get_txs(3).unwrap();
+
+    // Test: the transaction following the rejected one is neither included nor rejected.
+    let args = CommitBlockArgs {
+        state_changes: HashMap::from([(
+            contract_address!("0x0"),
+            AccountState { nonce: Nonce(felt!(0_u8)), ..Default::default() },
+        )]),
+        included_tx_hashes: [input_nonce_0.tx.tx_hash].into(),
+        rejected_tx_hashes: [input_nonce_1.tx.tx_hash, unknown_tx_hash].into(),
+    };
+    mempool.commit_block(args).unwrap();
+
+    // Assert: the following transaction is no longer staged, and the account is forgotten.
+    assert_eq!(mempool.get_tx_status(input_nonce_1.tx.tx_hash), Ok(TransactionStatus::Rejected));
+    assert_eq!(mempool.get_tx_status(input_nonce_2.tx.tx_hash), Ok(TransactionStatus::Evicted));
+    assert_eq!(mempool._account_nonces(), &HashMap::new());
+    assert_eq!(mempool.check_invariants(), InvariantReport::default());
+
+    // Assert: a rejected transaction that was not staged is ignored.
+    assert_eq!(
+        mempool.get_tx_status(unknown_tx_hash),
+        Err(MempoolError::TransactionNotFound { tx_hash: unknown_tx_hash })
+    );
+}
+
+#[rstest]
+fn test_commit_block_discards_committed_staged_txs(mut mempool: Mempool) {
+    // Setup.
+    let input_nonce_0 =
+        add_tx_input!(tx_hash: 1, sender_address: "0x0", tx_nonce: 0_u8, account_nonce: 0_u8);
+    let input_nonce_1 =
+        add_tx_input!(tx_hash: 2, sender_address: "0x0", tx_nonce: 1_u8, account_nonce: 0_u8);
+    let input_other_account =
+        add_tx_input!(tx_hash: 3, sender_address: "0x1", tx_nonce: 0_u8, account_nonce: 0_u8);
+
+    add_tx(&mut mempool, &input_nonce_0);
+    add_tx(&mut mempool, &input_nonce_1);
+    add_tx(&mut mempool, &input_other_account);
+    mempool.get_txs(3).unwrap();
+
+    // Test: both nonces of the account are committed, but only the first transaction is reported
+    // as included.
+    let args = CommitBlockArgs {
+        state_changes: HashMap::from([(
+            contract_address!("0x0"),
+            AccountState { nonce: Nonce(felt!(1_u8)), ..Default::default() },
+        )]),
+        included_tx_hashes: [input_nonce_0.tx.tx_hash].into(),
+        ..Default::default()
+    };
+    mempool.commit_block(args).unwrap();
+
+    // Assert: the transaction whose nonce was committed is no longer staged, and its account is
+    // forgotten.
+    assert_eq!(mempool.get_tx_status(input_nonce_1.tx.tx_hash), Ok(TransactionStatus::Evicted));
+    assert_eq!(
+        mempool.return_txs(vec![input_nonce_1.tx.clone()]),
+        Err(MempoolError::TransactionNotFound { tx_hash: input_nonce_1.tx.tx_hash })
+    );
+    assert_eq!(
+        mempool.get_tx_status(input_other_account.tx.tx_hash),
+        Ok(TransactionStatus::Staged)
+    );
+    assert_eq!(
+        mempool._account_nonces(),
+        &HashMap::from([(contract_address!("0x1"), Nonce(felt!(1_u8)))])
+    );
+    assert_eq!(mempool.check_invariants(), InvariantReport::default());
+}
+
+/// Retrieves all of the mempool's eligible transactions and commits them in a block, as the
+/// batcher would.
+fn get_txs_and_commit_block(mempool: &mut Mempool) -> Vec<ThinTransaction> {
+    let txs = mempool.get_txs(10).unwrap();
+    let included_tx_hashes = txs.iter().map(|tx| tx.tx_hash).collect();
+    assert!(mempool
+        .commit_block(CommitBlockArgs { included_tx_hashes, ..Default::default() })
+        .is_ok());
+    txs
+}
+
+#[rstest]
+fn test_revert_block_reinserts_txs(mut mempool: Mempool) {
+    // Setup.
+    let input_nonce_0 =
+        add_tx_input!(tx_hash: 1, sender_address: "0x0", tx_nonce: 0_u8, account_nonce: 0_u8);
+    let input_nonce_1 =
+        add_tx_input!(tx_hash: 2, sender_address: "0x0", tx_nonce: 1_u8, account_nonce: 0_u8);
+    add_tx(&mut mempool, &input_nonce_0);
+    add_tx(&mut mempool, &input_nonce_1);
+    let block_txs = get_txs_and_commit_block(&mut mempool);
+
+    // Test.
+    let restored_nonces = HashMap::from([(contract_address!("0x0"), Nonce(felt!(0_u8)))]);
+    assert!(mempool.revert_block(block_txs, restored_nonces).is_ok());
+
+    // Assert: the reverted transactions are held again, and the first one is eligible.
+    let expected_queue_txs = [TransactionReference::new(&input_nonce_0.tx)];
+    let expected_pool_txs = [input_nonce_0.tx.clone(), input_nonce_1.tx.clone()];
+    let expected_mempool_state = MempoolState::new(expected_pool_txs, expected_queue_txs);
+    expected_mempool_state.assert_eq_mempool_state(&mempool);
+    assert_eq!(mempool.get_tx_status(input_nonce_1.tx.tx_hash), Ok(TransactionStatus::Pending));
+
+    // Assert: the reverted transactions can be sequenced again.
+    assert_eq!(mempool.get_txs(2).unwrap(), &[input_nonce_0.tx, input_nonce_1.tx]);
+}
+
+#[rstest]
+fn test_revert_multiple_blocks(mut mempool: Mempool) {
+    // Setup.
+    let input_address_0_nonce_0 =
+        add_tx_input!(tx_hash: 1, sender_address: "0x0", tx_nonce: 0_u8, account_nonce: 0_u8);
+    let input_address_1_nonce_0 =
+        add_tx_input!(tx_hash: 2, sender_address: "0x1", tx_nonce: 0_u8, account_nonce: 0_u8);
+    let input_address_0_nonce_1 =
+        add_tx_input!(tx_hash: 3, sender_address: "0x0", tx_nonce: 1_u8, account_nonce: 0_u8);
+    let input_address_0_nonce_2 =
+        add_tx_input!(tx_hash: 4, sender_address: "0x0", tx_nonce: 2_u8, account_nonce: 0_u8);
+
+    add_tx(&mut mempool, &input_address_0_nonce_0);
+    add_tx(&mut mempool, &input_address_1_nonce_0);
+    let first_block_txs = get_txs_and_commit_block(&mut mempool);
+    add_tx(&mut mempool, &input_address_0_nonce_1);
+    let second_block_txs = get_txs_and_commit_block(&mut mempool);
+    add_tx(&mut mempool, &input_address_0_nonce_2);
+
+    // Test and assert: revert the latest block first.
+    let restored_nonces = HashMap::from([(contract_address!("0x0"), Nonce(felt!(1_u8)))]);
+    assert!(mempool.revert_block(second_block_txs, restored_nonces).is_ok());
+
+    let expected_queue_txs = [TransactionReference::new(&input_address_0_nonce_1.tx)];
+    let expected_pool_txs =
+        [input_address_0_nonce_1.tx.clone(), input_address_0_nonce_2.tx.clone()];
+    let expected_mempool_state = MempoolState::new(expected_pool_txs, expected_queue_txs);
+    expected_mempool_state.assert_eq_mempool_state(&mempool);
+
+    let restored_nonces = HashMap::from([
+        (contract_address!("0x0"), Nonce(felt!(0_u8))),
+        (contract_address!("0x1"), Nonce(felt!(0_u8))),
+    ]);
+    assert!(mempool.revert_block(first_block_txs, restored_nonces).is_ok());
+
+    let expected_queue_txs = [
+        TransactionReference::new(&input_address_1_nonce_0.tx),
+        TransactionReference::new(&input_address_0_nonce_0.tx),
+    ];
+    let expected_pool_txs = [
+        input_address_0_nonce_0.tx,
+        input_address_1_nonce_0.tx,
+        input_address_0_nonce_1.tx,
+        input_address_0_nonce_2.tx,
+    ];
+    let expected_mempool_state = MempoolState::new(expected_pool_txs, expected_queue_txs);
+    expected_mempool_state.assert_eq_mempool_state(&mempool);
+}
+
+#[rstest]
+fn test_revert_block_discards_invalid_txs(mut mempool: Mempool) {
+    // Setup.
+    let input_nonce_0 =
+        add_tx_input!(tx_hash: 1, sender_address: "0x0", tx_nonce: 0_u8, account_nonce: 0_u8);
+    let input_nonce_1 =
+        add_tx_input!(tx_hash: 2, sender_address: "0x0", tx_nonce: 1_u8, account_nonce: 0_u8);
+    add_tx(&mut mempool, &input_nonce_0);
+    add_tx(&mut mempool, &input_nonce_1);
+    let block_txs = get_txs_and_commit_block(&mut mempool);
+
+    // Test: the restored state already includes the account's first transaction.
+    let restored_nonces = HashMap::from([(contract_address!("0x0"), Nonce(felt!(1_u8)))]);
+    assert!(mempool.revert_block(block_txs, restored_nonces).is_ok());
+
+    // Assert.
+    let expected_queue_txs = [TransactionReference::new(&input_nonce_1.tx)];
+    let expected_mempool_state = MempoolState::new([input_nonce_1.tx], expected_queue_txs);
+    expected_mempool_state.assert_eq_mempool_state(&mempool);
+    assert_eq!(mempool.get_tx_status(input_nonce_0.tx.tx_hash), Ok(TransactionStatus::Evicted));
+}
+
+#[rstest]
+fn test_revert_block_discards_staged_txs(mut mempool: Mempool) {
+    // Setup.
+    let mut input_nonce_0 =
+        add_tx_input!(tx_hash: 1, sender_address: "0x0", tx_nonce: 0_u8, account_nonce: 0_u8);
+    let mut input_nonce_1 =
+        add_tx_input!(tx_hash: 2, sender_address: "0x0", tx_nonce: 1_u8, account_nonce: 1_u8);
+    let mut input_nonce_1_resubmitted =
+        add_tx_input!(tx_hash: 3, sender_address: "0x0", tx_nonce: 1_u8, account_nonce: 0_u8);
+    set_max_fee_and_balance(&mut input_nonce_0, 50, 100);
+    set_max_fee_and_balance(&mut input_nonce_1, 50, 100);
+    set_max_fee_and_balance(&mut input_nonce_1_resubmitted, 50, 100);
+
+    add_tx(&mut mempool, &input_nonce_0);
+    let block_txs = get_txs_and_commit_block(&mut mempool);
+    add_tx(&mut mempool, &input_nonce_1);
+    assert_eq!(mempool.get_txs(1).unwrap(), &[input_nonce_1.tx.clone()]);
+
+    // Test: the transaction following the reverted one is staged.
+    let restored_nonces = HashMap::from([(contract_address!("0x0"), Nonce(felt!(0_u8)))]);
+    mempool.revert_block(block_txs, restored_nonces).unwrap();
+
+    // Assert: the staged transaction is discarded, and the reverted one is eligible.
+    let expected_queue_txs = [TransactionReference::new(&input_nonce_0.tx)];
+    let expected_mempool_state = MempoolState::new([input_nonce_0.tx], expected_queue_txs);
+    expected_mempool_state.assert_eq_mempool_state(&mempool);
+    assert_eq!(mempool.get_tx_status(input_nonce_1.tx.tx_hash), Ok(TransactionStatus::Evicted));
+    assert_eq!(mempool.check_invariants(), InvariantReport::default());
+
+    // Assert: the fee of the discarded transaction is released, so that the account can afford a
+    // resubmitted transaction in its place.
+    add_tx(&mut mempool, &input_nonce_1_resubmitted);
+}
+
+#[rstest]
+fn test_snapshot_and_restore(mut mempool: Mempool) {
+    // Setup.
+    let input_address_0_nonce_1 =
+        add_tx_input!(tx_hash: 1, sender_address: "0x0", tx_nonce: 1_u8, account_nonce: 0_u8);
+    let input_address_1_nonce_3 =
+        add_tx_input!(tx_hash: 2, sender_address: "0x1", tx_nonce: 3_u8, account_nonce: 3_u8);
+    let input_address_0_nonce_0 =
+        add_tx_input!(tx_hash: 3, sender_address: "0x0", tx_nonce: 0_u8, account_nonce: 0_u8);
+    let input_address_1_nonce_5 =
+        add_tx_input!(tx_hash: 4, sender_address: "0x1", tx_nonce: 5_u8, account_nonce: 3_u8);
+
+    for input in [
+        &input_address_0_nonce_1,
+        &input_address_1_nonce_3,
+        &input_address_0_nonce_0,
+        &input_address_1_nonce_5,
+    ] {
+        add_tx(&mut mempool, input);
+    }
+
+    // Test.
+    let snapshot = mempool.snapshot();
+    let snapshot_file = tempfile::NamedTempFile::new().unwrap();
+    snapshot.write_to_file(snapshot_file.path()).unwrap();
+    let restored_snapshot = MempoolSnapshot::read_from_file(snapshot_file.path()).unwrap();
+    let mut restored_mempool = Mempool::restore(MempoolConfig::default(), restored_snapshot);
+
+    // Assert: the restored mempool holds the same transactions, and the same ones are eligible.
+    let expected_tx_hashes: Vec<TransactionHash> =
+        [1, 2, 3, 4].map(|tx_hash| TransactionHash(StarkHash::from(tx_hash))).to_vec();
+    let mut snapshot_tx_hashes: Vec<TransactionHash> =
+        snapshot.txs.iter().map(|snapshot_tx| snapshot_tx.tx.tx_hash).collect();
+    snapshot_tx_hashes.sort();
+    assert_eq!(snapshot_tx_hashes, expected_tx_hashes);
+
+    let expected_queue_txs = [
+        TransactionReference::new(&input_address_1_nonce_3.tx),
+        TransactionReference::new(&input_address_0_nonce_0.tx),
+    ];
+    let expected_pool_txs = [
+        input_address_0_nonce_0.tx.clone(),
+        input_address_0_nonce_1.tx.clone(),
+        input_address_1_nonce_3.tx.clone(),
+        input_address_1_nonce_5.tx,
+    ];
+    let expected_mempool_state = MempoolState::new(expected_pool_txs, expected_queue_txs);
+    expected_mempool_state.assert_eq_mempool_state(&restored_mempool);
+
+    // Assert: the nonce gap of the account is kept.
+    assert_eq!(
+        restored_mempool.get_txs(4).unwrap(),
+        &[input_address_0_nonce_0.tx, input_address_1_nonce_3.tx, input_address_0_nonce_1.tx]
+    );
+}
+
+#[rstest]
+fn test_snapshot_and_restore_keeps_arrival_order() {
+    // Setup.
+    let config =
+        MempoolConfig { tx_ordering_policy: TransactionOrderingPolicy::Fifo, ..Default::default() };
+    let mut mempool = Mempool::with_config(config.clone());
+    let input_address_0 = add_tx_input!(tip: 0, tx_hash: 1, sender_address: "0x0");
+    let input_address_1 = add_tx_input!(tip: 0, tx_hash: 2, sender_address: "0x1");
+    let input_address_2 = add_tx_input!(tip: 0, tx_hash: 3, sender_address: "0x2");
+    let input_after_restore = add_tx_input!(tip: 0, tx_hash: 4, sender_address: "0x3");
+    for input in [&input_address_0, &input_address_1, &input_address_2] {
+        add_tx(&mut mempool, input);
+    }
+
+    // The first transaction is reinserted into the pool after the others, but keeps its arrival.
+    let txs = mempool.get_txs(1).unwrap();
+    mempool.return_txs(txs).unwrap();
+
+    // Test.
+    let snapshot = mempool.snapshot();
+    let mut restored_mempool = Mempool::restore(config, snapshot.clone());
+    add_tx(&mut restored_mempool, &input_after_restore);
+
+    // Assert: the snapshot is ordered by arrival, and the restored transactions are retrieved by
+    // it, ahead of later arrivals.
+    let snapshot_tx_hashes: Vec<TransactionHash> =
+        snapshot.txs.iter().map(|snapshot_tx| snapshot_tx.tx.tx_hash).collect();
+    assert_eq!(
+        snapshot_tx_hashes,
+        [input_address_0.tx.tx_hash, input_address_1.tx.tx_hash, input_address_2.tx.tx_hash]
+    );
+    assert_eq!(
+        restored_mempool.get_txs(4).unwrap(),
+        [input_address_0.tx, input_address_1.tx, input_address_2.tx, input_after_restore.tx]
+    );
+}
+
+#[tokio::test]
+async fn test_write_snapshots_on_shutdown() {
+    // Setup.
+    let input =
+        add_tx_input!(tx_hash: 1, sender_address: "0x0", tx_nonce: 0_u8, account_nonce: 0_u8);
+    let mut mempool = Mempool::empty();
+    add_tx(&mut mempool, &input);
+    let snapshot_dir = tempfile::tempdir().unwrap();
+    let config = MempoolSnapshotConfig {
+        path: snapshot_dir.path().join("mempool_snapshot.json"),
+        flush_interval: Duration::from_secs(3600),
+    };
+
+    // Test: the node shuts down before the first interval elapses.
+    write_snapshots(Arc::new(Mutex::new(mempool)), config.clone(), async {}).await;
+
+    // Assert: a final snapshot is written.
+    let snapshot = MempoolSnapshot::read_from_file(&config.path).unwrap();
+    assert_eq!(snapshot.txs, [SnapshotTransaction { tx: input.tx, arrival: 0 }]);
+}
+
+#[rstest]
+fn test_snapshot_and_restore_account_behind_nonce_gap(mut mempool: Mempool) {
+    // Setup: the account's next nonce is 1, and its transaction is held behind a nonce gap.
+    let input_nonce_2 =
+        add_tx_input!(tx_hash: 1, sender_address: "0x0", tx_nonce: 2_u8, account_nonce: 1_u8);
+    add_tx(&mut mempool, &input_nonce_2);
+
+    // Test.
+    let snapshot = mempool.snapshot();
+    let restored_mempool = Mempool::restore(MempoolConfig::default(), snapshot.clone());
+
+    // Assert: the account's nonce is not recorded, and the transaction is held again once
+    // restored.
+    assert!(mempool._account_nonces().is_empty());
+    let expected_account_state = AccountState { nonce: Nonce(felt!(1_u8)), balance: 0 };
+    assert_eq!(
+        snapshot.accounts,
+        [Account {
+            sender_address: input_nonce_2.tx.sender_address,
+            state: expected_account_state
+        }]
+    );
+    let expected_mempool_state = MempoolState::new([input_nonce_2.tx], []);
+    expected_mempool_state.assert_eq_mempool_state(&restored_mempool);
+}
+
+#[rstest]
+fn test_add_tx_rejects_nonce_preceding_queued_tx(mut mempool: Mempool) {
+    // Setup.
+    let input_nonce_1 =
+        add_tx_input!(tx_hash: 1, sender_address: "0x0", tx_nonce: 1_u8, account_nonce: 1_u8);
+    add_tx(&mut mempool, &input_nonce_1);
+
+    // Test: a transaction provided with an outdated account state.
+    let input_nonce_0 =
+        add_tx_input!(tx_hash: 2, sender_address: "0x0", tx_nonce: 0_u8, account_nonce: 0_u8);
+    let result = mempool.add_tx(input_nonce_0);
+
+    // Assert: the account's next nonce is that of its queued transaction.
+    assert_eq!(
+        result,
+        Err(MempoolError::NonceTooOld {
+            tx_hash: TransactionHash(StarkHash::TWO),
+            tx_nonce: Nonce(felt!(0_u8)),
+            account_nonce: Nonce(felt!(1_u8)),
+        })
+    );
+}
+
+#[rstest]
+fn test_subscribe(mut mempool: Mempool) {
+    // Setup.
+    let input = add_tx_input!(tip: 90, tx_hash: 1, sender_address: "0x0");
+    let mut input_escalated_fees = add_tx_input!(tip: 100, tx_hash: 2, sender_address: "0x0");
+    input_escalated_fees.tx.resource_bounds.l1_gas.max_price_per_unit = 1;
+    let tx_hash = input.tx.tx_hash;
+    let escalated_tx_hash = input_escalated_fees.tx.tx_hash;
+
+    let mut events = mempool.subscribe();
+
+    // Test.
+    add_tx(&mut mempool, &input);
+    add_tx(&mut mempool, &input_escalated_fees);
+    mempool.get_txs(1).unwrap();
+    let included_tx_hashes = [escalated_tx_hash].into();
+    assert!(mempool
+        .commit_block(CommitBlockArgs { included_tx_hashes, ..Default::default() })
+        .is_ok());
+
+    // Assert.
+    let expected_events = [
+        MempoolEvent::TransactionAdded(input.tx),
+        MempoolEvent::TransactionReplaced { replaced_tx_hash: tx_hash, tx_hash: escalated_tx_hash },
+        MempoolEvent::TransactionAdded(input_escalated_fees.tx),
+        MempoolEvent::TransactionStaged(escalated_tx_hash),
+        MempoolEvent::TransactionIncluded(escalated_tx_hash),
+    ];
+    for expected_event in expected_events {
+        assert_eq!(events.try_recv(), Ok(expected_event));
+    }
+    assert!(events.is_empty());
+}
+
+#[rstest]
+fn test_metrics(mut mempool: Mempool) {
+    // Setup.
+    let input_nonce_0 =
+        add_tx_input!(tx_hash: 1, sender_address: "0x0", tx_nonce: 0_u8, account_nonce: 0_u8);
+    let input_nonce_1 =
+        add_tx_input!(tx_hash: 2, sender_address: "0x0", tx_nonce: 1_u8, account_nonce: 0_u8);
+    let input_other_account =
+        add_tx_input!(tx_hash: 3, sender_address: "0x1", tx_nonce: 0_u8, account_nonce: 0_u8);
+
+    // Test.
+    for input in [&input_nonce_0, &input_nonce_1, &input_other_account] {
+        add_tx(&mut mempool, input);
+    }
+    assert_matches!(mempool.add_tx(input_nonce_0), Err(MempoolError::DuplicateTransaction { .. }));
+    mempool.get_txs(1).unwrap();
+
+    // Assert.
+    let metrics = mempool.metrics();
+    for expected_line in [
+        "mempool_pool_size 2",
+        "mempool_accounts 1",
+        "mempool_queue_size 1",
+        "mempool_staged_txs 1",
+        "mempool_added_txs_total 3",
+        "mempool_rejected_txs_total{reason=\"DuplicateTransaction\"} 1",
+        "mempool_get_txs_chunk_size_bucket{le=\"1.0\"} 1",
+        "mempool_get_txs_chunk_size_count 1",
+        "mempool_time_in_mempool_seconds_count 1",
+    ] {
+        assert!(metrics.lines().any(|line| line == expected_line), "{expected_line}\n{metrics}");
+    }
+}
+
+#[rstest]
+fn test_check_invariants_reports_violations() {
+    // Setup: a queued transaction that is not held in the pool.
+    let tx =
+        add_tx_input!(tx_hash: 1, sender_address: "0x0", tx_nonce: 0_u8, account_nonce: 0_u8).tx;
+    let mempool: Mempool = MempoolState::new([], [TransactionReference::new(&tx)]).into();
+
+    // Test.
+    let report = mempool.check_invariants();
+
+    // Assert.
+    let expected_violations = [
+        InvariantViolation::QueuedTransactionNotInPool { tx_hash: tx.tx_hash },
+        InvariantViolation::QueuedNonceMismatch {
+            tx_hash: tx.tx_hash,
+            queued_nonce: tx.nonce,
+            account_nonce: None,
+        },
+    ];
+    assert!(!report.is_ok());
+    assert_eq!(report.violations, expected_violations);
+}
+
+#[rstest]
+fn test_check_invariants_derives_next_nonce_from_pool() {
+    // Setup: accounts without staged or committed transactions, whose next nonces are not
+    // recorded; the second account's queued transaction is not its first one in the pool.
+    let input_address_0 =
+        add_tx_input!(tx_hash: 1, sender_address: "0x0", tx_nonce: 2_u8, account_nonce: 2_u8);
+    let input_address_1_nonce_0 =
+        add_tx_input!(tx_hash: 2, sender_address: "0x1", tx_nonce: 0_u8, account_nonce: 0_u8);
+    let input_address_1_nonce_1 =
+        add_tx_input!(tx_hash: 3, sender_address: "0x1", tx_nonce: 1_u8, account_nonce: 0_u8);
+    let pool_txs = [&input_address_0, &input_address_1_nonce_0, &input_address_1_nonce_1]
+        .map(|input| input.tx.clone());
+    let queue_txs = [&input_address_0, &input_address_1_nonce_1]
+        .map(|input| TransactionReference::new(&input.tx));
+    let mempool: Mempool = MempoolState::new(pool_txs, queue_txs).into();
+
+    // Test.
+    let report = mempool.check_invariants();
+
+    // Assert: only the second account's queued transaction is reported.
+    let expected_violations = [InvariantViolation::QueuedNonceMismatch {
+        tx_hash: input_address_1_nonce_1.tx.tx_hash,
+        queued_nonce: input_address_1_nonce_1.tx.nonce,
+        account_nonce: Some(input_address_1_nonce_0.tx.nonce),
+    }];
+    assert_eq!(report.violations, expected_violations);
+}
+
+#[rstest]
+fn test_add_tx_depending_on_deploy_account(mut mempool: Mempool) {
+    // Setup.
+    let deploy_account_input = into_deploy_account_input(
+        add_tx_input!(tx_hash: 1, sender_address: "0x0", tx_nonce: 0_u8, account_nonce: 0_u8),
+    );
+    let dependent_input = MempoolInput {
+        depends_on_deploy_account: true,
+        ..add_tx_input!(tx_hash: 2, sender_address: "0x0", tx_nonce: 1_u8, account_nonce: 0_u8)
+    };
+
+    // Test and assert: the dependent transaction is only accepted once the deploy account
+    // transaction is pending.
+    assert_eq!(
+        mempool.add_tx(dependent_input.clone()),
+        Err(MempoolError::MissingDeployAccount { tx_hash: dependent_input.tx.tx_hash })
+    );
+
+    add_tx(&mut mempool, &deploy_account_input);
+    add_tx(&mut mempool, &dependent_input);
+
+    let expected_queue_txs = [TransactionReference::new(&deploy_account_input.tx)];
+    let expected_pool_txs = [deploy_account_input.tx, dependent_input.tx];
+    let expected_mempool_state = MempoolState::new(expected_pool_txs, expected_queue_txs);
+    expected_mempool_state.assert_eq_mempool_state(&mempool);
+}
+
+#[rstest]
+fn test_evicting_deploy_account_evicts_dependent_txs() {
+    // Setup.
+    let clock = Arc::new(FakeClock::default());
+    let tx_ttl = Duration::from_secs(60);
+    let mut mempool = Mempool::with_config_and_clock(
+        MempoolConfig { tx_ttl, ..Default::default() },
+        clock.clone(),
+    );
+    let deploy_account_input = into_deploy_account_input(
+        add_tx_input!(tx_hash: 1, sender_address: "0x0", tx_nonce: 0_u8, account_nonce: 0_u8),
+    );
+    let dependent_input = MempoolInput {
+        depends_on_deploy_account: true,
+        ..add_tx_input!(tx_hash: 2, sender_address: "0x0", tx_nonce: 1_u8, account_nonce: 0_u8)
+    };
+    let input =
+        add_tx_input!(tx_hash: 3, sender_address: "0x1", tx_nonce: 0_u8, account_nonce: 0_u8);
+
+    add_tx(&mut mempool, &deploy_account_input);
+    clock.advance(Duration::from_secs(30));
+    add_tx(&mut mempool, &dependent_input);
+    add_tx(&mut mempool, &input);
+
+    // Test: only the deploy account transaction expires.
+    mempool.evict_expired(clock.now() + Duration::from_secs(30)).unwrap();
+
+    // Assert: the dependent transaction is evicted along with the deploy account transaction.
+    assert_eq!(mempool.get_tx_status(dependent_input.tx.tx_hash), Ok(TransactionStatus::Evicted));
+    let expected_queue_txs = [TransactionReference::new(&input.tx)];
+    let expected_pool_txs = [input.tx];
+    let expected_mempool_state = MempoolState::new(expected_pool_txs, expected_queue_txs);
+    expected_mempool_state.assert_eq_mempool_state(&mempool);
+}
+
+#[rstest]
+fn test_rejecting_deploy_account_evicts_dependent_txs(mut mempool: Mempool) {
+    // Setup.
+    let deploy_account_input = into_deploy_account_input(
+        add_tx_input!(tx_hash: 1, sender_address: "0x0", tx_nonce: 0_u8, account_nonce: 0_u8),
+    );
+    let staged_dependent_input = MempoolInput {
+        depends_on_deploy_account: true,
+        ..add_tx_input!(tx_hash: 2, sender_address: "0x0", tx_nonce: 1_u8, account_nonce: 0_u8)
+    };
+    let dependent_input = MempoolInput {
+        depends_on_deploy_account: true,
+        ..add_tx_input!(tx_hash: 3, sender_address: "0x0", tx_nonce: 2_u8, account_nonce: 0_u8)
+    };
+    let input =
+        add_tx_input!(tx_hash: 4, sender_address: "0x1", tx_nonce: 0_u8, account_nonce: 0_u8);
+
+    for input in [&deploy_account_input, &staged_dependent_input, &dependent_input] {
+        add_tx(&mut mempool, input);
+    }
+    let txs = mempool.get_txs(2).unwrap();
+    assert_eq!(txs, [deploy_account_input.tx.clone(), staged_dependent_input.tx.clone()]);
+    add_tx(&mut mempool, &input);
+
+    // Test.
+    let args = CommitBlockArgs {
+        rejected_tx_hashes: [deploy_account_input.tx.tx_hash].into(),
+        ..Default::default()
+    };
+    mempool.commit_block(args).unwrap();
+
+    // Assert: the dependent transactions are evicted along with the rejected deploy account
+    // transaction.
+    for tx in [&staged_dependent_input.tx, &dependent_input.tx] {
+        assert_eq!(mempool.get_tx_status(tx.tx_hash), Ok(TransactionStatus::Evicted));
+    }
+    let expected_queue_txs = [TransactionReference::new(&input.tx)];
+    let expected_pool_txs = [input.tx];
+    let expected_mempool_state = MempoolState::new(expected_pool_txs, expected_queue_txs);
+    expected_mempool_state.assert_eq_mempool_state(&mempool);
+    assert!(mempool._account_nonces().is_empty());
+    assert_eq!(mempool.check_invariants(), InvariantReport::default());
+}
+
+#[rstest]
+fn test_fifo_ordering_by_arrival_to_mempool() {
+    // Setup.
+    let mut mempool = Mempool::with_config(MempoolConfig {
+        tx_ordering_policy: TransactionOrderingPolicy::Fifo,
+        ..Default::default()
+    });
+    let input_address_0_nonce_0 =
+        add_tx_input!(tx_hash: 1, sender_address: "0x0", tx_nonce: 0_u8, account_nonce: 0_u8);
+    let input_address_0_nonce_1 =
+        add_tx_input!(tx_hash: 2, sender_address: "0x0", tx_nonce: 1_u8, account_nonce: 0_u8);
+    let input_address_1_nonce_0 =
+        add_tx_input!(tx_hash: 3, sender_address: "0x1", tx_nonce: 0_u8, account_nonce: 0_u8);
+    for input in [&input_address_0_nonce_0, &input_address_0_nonce_1, &input_address_1_nonce_0] {
+        add_tx(&mut mempool, input);
+    }
+
+    // Test: a retrieved transaction is returned, and is queued again.
+    let txs = mempool.get_txs(1).unwrap();
+    assert_eq!(txs, vec![input_address_0_nonce_0.tx.clone()]);
+    mempool.return_txs(txs).unwrap();
+
+    // Assert: transactions are retrieved by their arrival to the mempool, regardless of when they
+    // were queued.
+    for expected_tx in
+        [input_address_0_nonce_0.tx, input_address_0_nonce_1.tx, input_address_1_nonce_0.tx]
+    {
+        assert_eq!(mempool.get_txs(1).unwrap(), [expected_tx]);
+    }
+}
+
+#[rstest]
+fn test_get_txs_fairness_mode() {
+    // Setup.
+    let mut mempool = Mempool::with_config(MempoolConfig {
+        fairness_config: Some(FairnessConfig { max_txs_per_account: 2 }),
+        ..Default::default()
+    });
+    let input_address_2 =
+        add_tx_input!(tx_hash: 3, sender_address: "0x2", tx_nonce: 0_u8, account_nonce: 0_u8);
+    let input_address_0_nonce_0 =
+        add_tx_input!(tx_hash: 10, sender_address: "0x0", tx_nonce: 0_u8, account_nonce: 0_u8);
+    let input_address_0_nonce_1 =
+        add_tx_input!(tx_hash: 11, sender_address: "0x0", tx_nonce: 1_u8, account_nonce: 0_u8);
+    let input_address_0_nonce_2 =
+        add_tx_input!(tx_hash: 12, sender_address: "0x0", tx_nonce: 2_u8, account_nonce: 0_u8);
+    let input_address_1_nonce_0 =
+        add_tx_input!(tx_hash: 1, sender_address: "0x1", tx_nonce: 0_u8, account_nonce: 0_u8);
+    let input_address_1_nonce_1 =
+        add_tx_input!(tx_hash: 2, sender_address: "0x1", tx_nonce: 1_u8, account_nonce: 0_u8);
+
+    for input in [
+        &input_address_2,
+        &input_address_0_nonce_0,
+        &input_address_0_nonce_1,
+        &input_address_0_nonce_2,
+        &input_address_1_nonce_0,
+        &input_address_1_nonce_1,
+    ] {
+        add_tx(&mut mempool, input);
+    }
+
+    // Test and assert: equal-tip transactions are retrieved by arrival, round-robin across
+    // accounts, and each account contributes up to 2 transactions per call.
+    let txs = mempool.get_txs(10).unwrap();
+    let expected_txs = [
+        input_address_2.tx,
+        input_address_0_nonce_0.tx,
+        input_address_1_nonce_0.tx,
+        input_address_0_nonce_1.tx,
+        input_address_1_nonce_1.tx,
+    ];
+    assert_eq!(txs, expected_txs);
+
+    let txs = mempool.get_txs(10).unwrap();
+    assert_eq!(txs, [input_address_0_nonce_2.tx]);
+}
+
+#[rstest]
+fn test_get_txs_fairness_mode_requeues_deferred_txs() {
+    // Setup.
+    let mut mempool = Mempool::with_config(MempoolConfig {
+        fairness_config: Some(FairnessConfig { max_txs_per_account: 1 }),
+        ..Default::default()
+    });
+    let input_address_0_nonce_0 = add_tx_input!(tip: 20, tx_hash: 1, sender_address: "0x0");
+    let input_address_0_nonce_1 = add_tx_input!(tip: 20, tx_hash: 2, sender_address: "0x0",
+        tx_nonce: 1_u8, account_nonce: 0_u8);
+    let input_address_1_nonce_0 = add_tx_input!(tip: 10, tx_hash: 3, sender_address: "0x1");
+    for input in [&input_address_0_nonce_0, &input_address_0_nonce_1, &input_address_1_nonce_0] {
+        add_tx(&mut mempool, input);
+    }
+
+    // Test: the account's next transaction is deferred, as the account reached its limit.
+    let txs = mempool.get_txs(10).unwrap();
+
+    // Assert: the deferred transaction is queued again once the retrieval ends.
+    assert_eq!(txs, [input_address_0_nonce_0.tx, input_address_1_nonce_0.tx]);
+    assert_eq_mempool_queue(&mempool, std::slice::from_ref(&input_address_0_nonce_1.tx));
+    assert_eq!(mempool.get_txs(10).unwrap(), [input_address_0_nonce_1.tx]);
+}
+
+#[rstest]
+fn test_get_tx_status(mut mempool: Mempool) {
+    // Setup.
+    let input_nonce_0 =
+        add_tx_input!(tx_hash: 1, sender_address: "0x0", tx_nonce: 0_u8, account_nonce: 0_u8);
+    let input_nonce_1 =
+        add_tx_input!(tx_hash: 2, sender_address: "0x0", tx_nonce: 1_u8, account_nonce: 0_u8);
+    let input_nonce_2 =
+        add_tx_input!(tx_hash: 3, sender_address: "0x0", tx_nonce: 2_u8, account_nonce: 0_u8);
+    let [tx_hash_0, tx_hash_1, tx_hash_2] =
+        [&input_nonce_0, &input_nonce_1, &input_nonce_2].map(|input| input.tx.tx_hash);
+
+    for input in [&input_nonce_0, &input_nonce_1, &input_nonce_2] {
+        add_tx(&mut mempool, input);
+    }
+
+    // Test and assert.
+    assert_eq!(mempool.get_tx_status(tx_hash_0), Ok(TransactionStatus::Queued));
+    assert_eq!(mempool.get_tx_status(tx_hash_1), Ok(TransactionStatus::Pending));
+
+    mempool.get_txs(2).unwrap();
+    assert_eq!(mempool.get_tx_status(tx_hash_0), Ok(TransactionStatus::Staged));
+    assert_eq!(mempool.get_tx_status(tx_hash_1), Ok(TransactionStatus::Staged));
+    assert_eq!(mempool.get_tx_status(tx_hash_2), Ok(TransactionStatus::Queued));
+
+    let args = CommitBlockArgs {
+        state_changes: HashMap::from([(
+            contract_address!("0x0"),
+            AccountState { nonce: Nonce(felt!(0_u8)), ..Default::default() },
+        )]),
+        included_tx_hashes: [tx_hash_0].into(),
+        rejected_tx_hashes: [tx_hash_1].into(),
+    };
+    mempool.commit_block(args).unwrap();
+    assert_eq!(mempool.get_tx_status(tx_hash_0), Ok(TransactionStatus::Included));
+    assert_eq!(mempool.get_tx_status(tx_hash_1), Ok(TransactionStatus::Rejected));
+    assert_eq!(mempool.get_tx_status(tx_hash_2), Ok(TransactionStatus::Pending));
+
+    let unknown_tx_hash = TransactionHash(StarkHash::from(4_u8));
+    assert_eq!(
+        mempool.get_tx_status(unknown_tx_hash),
+        Err(MempoolError::TransactionNotFound { tx_hash: unknown_tx_hash })
+    );
+}
+
+#[rstest]
+fn test_get_tx_status_of_evicted_tx() {
+    // Setup.
+    let mut mempool = Mempool::with_config(MempoolConfig { max_txs: 1, ..Default::default() });
+    let input_tip_10 = add_tx_input!(tip: 10, tx_hash: 1, sender_address: "0x0");
+    let input_tip_20 = add_tx_input!(tip: 20, tx_hash: 2, sender_address: "0x1");
+
+    add_tx(&mut mempool, &input_tip_10);
+    add_tx(&mut mempool, &input_tip_20);
+
+    // Test and assert.
+    assert_eq!(mempool.get_tx_status(input_tip_10.tx.tx_hash), Ok(TransactionStatus::Evicted));
+}
+
+#[rstest]
+fn test_add_tx_insufficient_balance(mut mempool: Mempool) {
+    // Setup.
+    let mut input_nonce_0 =
+        add_tx_input!(tx_hash: 1, sender_address: "0x0", tx_nonce: 0_u8, account_nonce: 0_u8);
+    set_max_fee_and_balance(&mut input_nonce_0, 60, 100);
+    let mut input_nonce_1 =
+        add_tx_input!(tx_hash: 2, sender_address: "0x0", tx_nonce: 1_u8, account_nonce: 0_u8);
+    set_max_fee_and_balance(&mut input_nonce_1, 60, 100);
+
+    add_tx(&mut mempool, &input_nonce_0);
+
+    // Test.
+    assert_eq!(
+        mempool.add_tx(input_nonce_1),
+        Err(MempoolError::InsufficientBalance { tx_hash: TransactionHash(StarkHash::TWO) })
+    );
+
+    // Assert: the account's affordable transaction is kept.
+    let expected_queue_txs = [TransactionReference::new(&input_nonce_0.tx)];
+    let expected_mempool_state = MempoolState::new([input_nonce_0.tx], expected_queue_txs);
+    expected_mempool_state.assert_eq_mempool_state(&mempool);
+}
+
+#[rstest]
+fn test_rejected_tx_does_not_update_balance(mut mempool: Mempool) {
+    // Setup.
+    let mut input_nonce_0 =
+        add_tx_input!(tx_hash: 1, sender_address: "0x0", tx_nonce: 0_u8, account_nonce: 0_u8);
+    set_max_fee_and_balance(&mut input_nonce_0, 60, 100);
+    let mut input_nonce_1 =
+        add_tx_input!(tx_hash: 2, sender_address: "0x0", tx_nonce: 1_u8, account_nonce: 0_u8);
+    set_max_fee_and_balance(&mut input_nonce_1, 60, 0);
+
+    add_tx(&mut mempool, &input_nonce_0);
+
+    // Test.
+    assert_eq!(
+        mempool.add_tx(input_nonce_1),
+        Err(MempoolError::InsufficientBalance { tx_hash: TransactionHash(StarkHash::TWO) })
+    );
+
+    // Assert: the account's balance is that of its accepted transaction.
+    let accounts = mempool.snapshot().accounts;
+    assert_eq!(accounts, [input_nonce_0.account]);
+}
+
+#[rstest]
+fn test_fee_escalation_excludes_replaced_tx_fee(mut mempool: Mempool) {
+    // Setup.
+    let mut input = add_tx_input!(tip: 10, tx_hash: 1, sender_address: "0x0");
+    set_max_fee_and_balance(&mut input, 60, 100);
+    let mut input_escalated_fees = add_tx_input!(tip: 20, tx_hash: 2, sender_address: "0x0");
+    set_max_fee_and_balance(&mut input_escalated_fees, 90, 100);
+
+    add_tx(&mut mempool, &input);
+
+    // Test.
+    add_tx(&mut mempool, &input_escalated_fees);
+
+    // Assert.
+    let expected_queue_txs = [TransactionReference::new(&input_escalated_fees.tx)];
+    let expected_mempool_state = MempoolState::new([input_escalated_fees.tx], expected_queue_txs);
+    expected_mempool_state.assert_eq_mempool_state(&mempool);
+}
+
+#[rstest]
+fn test_commit_block_releases_fees_of_included_txs(mut mempool: Mempool) {
+    // Setup.
+    let mut input_nonce_0 =
+        add_tx_input!(tx_hash: 1, sender_address: "0x0", tx_nonce: 0_u8, account_nonce: 0_u8);
+    set_max_fee_and_balance(&mut input_nonce_0, 60, 100);
+    let mut input_nonce_1 =
+        add_tx_input!(tx_hash: 2, sender_address: "0x0", tx_nonce: 1_u8, account_nonce: 0_u8);
+    set_max_fee_and_balance(&mut input_nonce_1, 40, 40);
+
+    add_tx(&mut mempool, &input_nonce_0);
+    mempool.get_txs(1).unwrap();
+
+    // Test.
+    let args = CommitBlockArgs {
+        state_changes: HashMap::from([(
+            contract_address!("0x0"),
+            AccountState { nonce: Nonce(felt!(0_u8)), balance: 40 },
+        )]),
+        included_tx_hashes: [input_nonce_0.tx.tx_hash].into(),
+        ..Default::default()
+    };
+    mempool.commit_block(args).unwrap();
+
+    // Assert: the remaining balance covers the account's next transaction.
+    add_tx(&mut mempool, &input_nonce_1);
+}
+
+#[rstest]
+fn test_add_tx_exceeds_max_txs_per_account() {
+    // Setup.
+    let mut mempool =
+        Mempool::with_config(MempoolConfig { max_txs_per_account: 2, ..Default::default() });
+    let input_nonce_0 =
+        add_tx_input!(tx_hash: 1, sender_address: "0x0", tx_nonce: 0_u8, account_nonce: 0_u8);
+    let input_nonce_1 =
+        add_tx_input!(tx_hash: 2, sender_address: "0x0", tx_nonce: 1_u8, account_nonce: 0_u8);
+    let input_nonce_2 =
+        add_tx_input!(tx_hash: 3, sender_address: "0x0", tx_nonce: 2_u8, account_nonce: 0_u8);
+    let input_other_account = add_tx_input!(tip: 1, tx_hash: 4, sender_address: "0x1");
+
+    add_tx(&mut mempool, &input_nonce_0);
+    add_tx(&mut mempool, &input_nonce_1);
+
+    // Test and assert.
+    assert_eq!(
+        mempool.add_tx(input_nonce_2),
+        Err(MempoolError::TooManyAccountTransactions {
+            tx_hash: TransactionHash(StarkHash::THREE),
+            max_txs_per_account: 2
+        })
+    );
+    add_tx(&mut mempool, &input_other_account);
+}
+
+#[rstest]
+fn test_add_tx_nonce_too_far_ahead() {
+    // Setup.
+    let mut mempool =
+        Mempool::with_config(MempoolConfig { max_nonce_distance: 2, ..Default::default() });
+    let input_nonce_3 =
+        add_tx_input!(tx_hash: 1, sender_address: "0x0", tx_nonce: 3_u8, account_nonce: 1_u8);
+    let input_nonce_4 =
+        add_tx_input!(tx_hash: 2, sender_address: "0x0", tx_nonce: 4_u8, account_nonce: 1_u8);
+
+    // Test and assert.
+    add_tx(&mut mempool, &input_nonce_3);
+    assert_eq!(
+        mempool.add_tx(input_nonce_4),
+        Err(MempoolError::NonceTooFarAhead {
+            tx_hash: TransactionHash(StarkHash::TWO),
+            tx_nonce: Nonce(felt!(4_u8)),
+            account_nonce: Nonce(felt!(1_u8)),
+        })
+    );
+}
+
+#[rstest]
+fn test_fee_escalation_valid_replacement(mut mempool: Mempool) {
+    // Setup.
+    let input = add_tx_input!(tip: 90, tx_hash: 1, sender_address: "0x0");
+    let mut input_escalated_fees = add_tx_input!(tip: 100, tx_hash: 2, sender_address: "0x0");
+    input_escalated_fees.tx.resource_bounds.l1_gas.max_price_per_unit = 1;
+
+    // Test.
+    add_tx(&mut mempool, &input);
+    add_tx(&mut mempool, &input_escalated_fees);
+
+    // Assert: the original transaction was replaced in both the pool and the queue.
+    let expected_queue_txs = [TransactionReference::new(&input_escalated_fees.tx)];
+    let expected_pool_txs = [input_escalated_fees.tx];
+    let expected_mempool_state = MempoolState::new(expected_pool_txs, expected_queue_txs);
+    expected_mempool_state.assert_eq_mempool_state(&mempool);
+}
+
+#[rstest]
+#[case::tip_not_escalated(105, 110)]
+#[case::l1_gas_price_not_escalated(110, 105)]
+#[case::identical_fees(100, 100)]
+fn test_fee_escalation_invalid_replacement(
+    mut mempool: Mempool,
+    #[case] tip: u64,
+    #[case] max_l1_gas_price: u128,
+) {
+    // Setup.
+    let mut input = add_tx_input!(tip: 100, tx_hash: 1, sender_address: "0x0");
+    input.tx.resource_bounds.l1_gas.max_price_per_unit = 100;
+    let mut input_same_nonce = add_tx_input!(tip: tip, tx_hash: 2, sender_address: "0x0");
+    input_same_nonce.tx.resource_bounds.l1_gas.max_price_per_unit = max_l1_gas_price;
+
+    add_tx(&mut mempool, &input);
+
+    // Test.
+    assert_eq!(
+        mempool.add_tx(input_same_nonce),
+        Err(MempoolError::InsufficientFeeEscalation {
+            tx_hash: TransactionHash(StarkHash::TWO),
+            existing_tx_hash: TransactionHash(StarkHash::ONE),
+        })
+    );
+
+    // Assert: the original transaction remains in the mempool.
+    let expected_queue_txs = [TransactionReference::new(&input.tx)];
+    let expected_pool_txs = [input.tx];
+    let expected_mempool_state = MempoolState::new(expected_pool_txs, expected_queue_txs);
+    expected_mempool_state.assert_eq_mempool_state(&mempool);
+}
+
+#[rstest]
+fn test_fee_escalation_of_non_queued_tx(mut mempool: Mempool) {
+    // Setup.
+    let input_nonce_1 = add_tx_input!(tip: 10, tx_hash: 1, sender_address: "0x0", tx_nonce: 1_u8,
+        account_nonce: 0_u8);
+    let input_nonce_1_escalated_fees = add_tx_input!(tip: 20, tx_hash: 2, sender_address: "0x0",
+        tx_nonce: 1_u8, account_nonce: 0_u8);
+
+    // Test.
+    add_tx(&mut mempool, &input_nonce_1);
+    add_tx(&mut mempool, &input_nonce_1_escalated_fees);
+
+    // Assert: only the pool is affected by the replacement.
+    let expected_pool_txs = [input_nonce_1_escalated_fees.tx];
+    let expected_mempool_state = MempoolState::new(expected_pool_txs, []);
+    expected_mempool_state.assert_eq_mempool_state(&mempool);
+}
+
+#[rstest]
+fn test_add_tx_evicts_lowest_tip_when_full() {
+    // Setup.
+    let mut mempool = Mempool::with_config(MempoolConfig { max_txs: 2, ..Default::default() });
+    let input_tip_10 = add_tx_input!(tip: 10, tx_hash: 1, sender_address: "0x0");
+    let input_tip_20 = add_tx_input!(tip: 20, tx_hash: 2, sender_address: "0x1");
+    let input_tip_30 = add_tx_input!(tip: 30, tx_hash: 3, sender_address: "0x2");
+
+    add_tx(&mut mempool, &input_tip_10);
+    add_tx(&mut mempool, &input_tip_20);
+
+    // Test.
+    add_tx(&mut mempool, &input_tip_30);
+
+    // Assert: the lowest-tip transaction was evicted.
+    let expected_queue_txs =
+        [TransactionReference::new(&input_tip_20.tx), TransactionReference::new(&input_tip_30.tx)];
+    let expected_pool_txs = [input_tip_20.tx, input_tip_30.tx];
+    let expected_mempool_state = MempoolState::new(expected_pool_txs, expected_queue_txs);
+    expected_mempool_state.assert_eq_mempool_state(&mempool);
+}
+
+#[rstest]
+fn test_add_tx_evicts_highest_nonce_first() {
+    // Setup.
+    let mut mempool = Mempool::with_config(MempoolConfig { max_txs: 2, ..Default::default() });
+    let input_nonce_0 = add_tx_input!(tip: 10, tx_hash: 1, sender_address: "0x0", tx_nonce: 0_u8,
+        account_nonce: 0_u8);
+    let input_nonce_1 = add_tx_input!(tip: 20, tx_hash: 2, sender_address: "0x0", tx_nonce: 1_u8,
+        account_nonce: 0_u8);
+    let input_other_account = add_tx_input!(tip: 30, tx_hash: 3, sender_address: "0x1");
+
+    add_tx(&mut mempool, &input_nonce_0);
+    add_tx(&mut mempool, &input_nonce_1);
+
+    // Test.
+    add_tx(&mut mempool, &input_other_account);
+
+    // Assert: the account's queued transaction is kept, to avoid creating a nonce gap.
+    let expected_queue_txs = [
+        TransactionReference::new(&input_nonce_0.tx),
+        TransactionReference::new(&input_other_account.tx),
+    ];
+    let expected_pool_txs = [input_nonce_0.tx, input_other_account.tx];
+    let expected_mempool_state = MempoolState::new(expected_pool_txs, expected_queue_txs);
+    expected_mempool_state.assert_eq_mempool_state(&mempool);
+}
+
+#[rstest]
+#[case::lower_tip(5)]
+#[case::equal_tip(10)]
+fn test_add_tx_mempool_full(#[case] tip: u64) {
+    // Setup.
+    let mut mempool = Mempool::with_config(MempoolConfig { max_txs: 1, ..Default::default() });
+    let input = add_tx_input!(tip: 10, tx_hash: 1, sender_address: "0x0");
+    let input_not_outbidding = add_tx_input!(tip: tip, tx_hash: 2, sender_address: "0x1");
+
+    add_tx(&mut mempool, &input);
+
+    // Test.
+    assert_eq!(
+        mempool.add_tx(input_not_outbidding),
+        Err(MempoolError::MempoolFull { tx_hash: TransactionHash(StarkHash::TWO) })
+    );
+
+    // Assert: the mempool is unchanged.
+    let expected_queue_txs = [TransactionReference::new(&input.tx)];
+    let expected_mempool_state = MempoolState::new([input.tx], expected_queue_txs);
+    expected_mempool_state.assert_eq_mempool_state(&mempool);
+}
+
+#[rstest]
+fn test_add_tx_duplicate_tx_does_not_evict() {
+    // Setup.
+    let mut mempool = Mempool::with_config(MempoolConfig { max_txs: 2, ..Default::default() });
+    let input_tip_10 = add_tx_input!(tip: 10, tx_hash: 1, sender_address: "0x0");
+    let input_tip_20 = add_tx_input!(tip: 20, tx_hash: 2, sender_address: "0x1");
+    // Outbids the lowest-tip transaction, but reuses the hash of a held transaction.
+    let duplicate_input = add_tx_input!(tip: 30, tx_hash: 2, sender_address: "0x2");
+
+    add_tx(&mut mempool, &input_tip_10);
+    add_tx(&mut mempool, &input_tip_20);
+
+    // Test.
+    assert_eq!(
+        mempool.add_tx(duplicate_input),
+        Err(MempoolError::DuplicateTransaction { tx_hash: TransactionHash(StarkHash::TWO) })
+    );
+
+    // Assert: the mempool is unchanged.
+    let expected_queue_txs =
+        [TransactionReference::new(&input_tip_10.tx), TransactionReference::new(&input_tip_20.tx)];
+    let expected_pool_txs = [input_tip_10.tx, input_tip_20.tx];
+    let expected_mempool_state = MempoolState::new(expected_pool_txs, expected_queue_txs);
+    expected_mempool_state.assert_eq_mempool_state(&mempool);
+}
+
+#[rstest]
+fn test_add_tx_does_not_evict_own_account_txs() {
+    // Setup.
+    let mut mempool = Mempool::with_config(MempoolConfig { max_txs: 1, ..Default::default() });
+    let input_nonce_0 = add_tx_input!(tip: 10, tx_hash: 1, sender_address: "0x0", tx_nonce: 0_u8,
+        account_nonce: 0_u8);
+    let input_nonce_1 = add_tx_input!(tip: 20, tx_hash: 2, sender_address: "0x0", tx_nonce: 1_u8,
+        account_nonce: 0_u8);
+
+    add_tx(&mut mempool, &input_nonce_0);
+
+    // Test and assert.
+    assert_eq!(
+        mempool.add_tx(input_nonce_1),
+        Err(MempoolError::MempoolFull { tx_hash: TransactionHash(StarkHash::TWO) })
+    );
+}
+
+#[rstest]
+fn test_add_tx_evicts_by_size() {
+    // Setup.
+    let input_tip_10 = add_tx_input!(tip: 10, tx_hash: 1, sender_address: "0x0");
+    let input_tip_20 = add_tx_input!(tip: 20, tx_hash: 2, sender_address: "0x1");
+    let max_size_in_bytes = estimated_tx_size(&input_tip_10.tx);
+    let mut mempool =
+        Mempool::with_config(MempoolConfig { max_size_in_bytes, ..Default::default() });
+
+    add_tx(&mut mempool, &input_tip_10);
+
+    // Test.
+    add_tx(&mut mempool, &input_tip_20);
+
+    // Assert.
+    let expected_queue_txs = [TransactionReference::new(&input_tip_20.tx)];
+    let expected_mempool_state = MempoolState::new([input_tip_20.tx], expected_queue_txs);
+    expected_mempool_state.assert_eq_mempool_state(&mempool);
+}
+
+#[rstest]
+fn test_add_tx_evicts_by_payload_size() {
+    // Setup.
+    let input_address_0 = add_tx_input!(tip: 10, tx_hash: 1, sender_address: "0x0");
+    let input_address_1 = add_tx_input!(tip: 10, tx_hash: 2, sender_address: "0x1");
+    let input_with_large_payload =
+        into_invoke_input(add_tx_input!(tip: 20, tx_hash: 3, sender_address: "0x2"), 100);
+    let max_size_in_bytes = estimated_tx_size(&input_with_large_payload.tx);
+    assert!(2 * estimated_tx_size(&input_address_0.tx) <= max_size_in_bytes);
+    let mut mempool =
+        Mempool::with_config(MempoolConfig { max_size_in_bytes, ..Default::default() });
+
+    add_tx(&mut mempool, &input_address_0);
+    add_tx(&mut mempool, &input_address_1);
+
+    // Test.
+    add_tx(&mut mempool, &input_with_large_payload);
+
+    // Assert: the transaction's payload takes the place of both transactions.
+    let expected_queue_txs = [TransactionReference::new(&input_with_large_payload.tx)];
+    let expected_mempool_state =
+        MempoolState::new([input_with_large_payload.tx], expected_queue_txs);
+    expected_mempool_state.assert_eq_mempool_state(&mempool);
+}
+
+#[rstest]
+fn test_evict_expired() {
+    // Setup.
+    let clock = Arc::new(FakeClock::default());
+    let tx_ttl = Duration::from_secs(60);
+    let mut mempool = Mempool::with_config_and_clock(
+        MempoolConfig { tx_ttl, ..Default::default() },
+        clock.clone(),
+    );
+    let input_address_0_nonce_0 =
+        add_tx_input!(tx_hash: 1, sender_address: "0x0", tx_nonce: 0_u8, account_nonce: 0_u8);
+    let input_address_0_nonce_1 =
+        add_tx_input!(tx_hash: 2, sender_address: "0x0", tx_nonce: 1_u8, account_nonce: 0_u8);
+    let input_address_1_nonce_0 =
+        add_tx_input!(tx_hash: 3, sender_address: "0x1", tx_nonce: 0_u8, account_nonce: 0_u8);
+
+    add_tx(&mut mempool, &input_address_0_nonce_0);
+    clock.advance(Duration::from_secs(30));
+    add_tx(&mut mempool, &input_address_0_nonce_1);
+    add_tx(&mut mempool, &input_address_1_nonce_0);
+
+    // Test.
+    mempool.evict_expired(clock.now() + Duration::from_secs(30)).unwrap();
+
+    // Assert: only the transaction inserted before the TTL elapsed expired, and the following
+    // transaction of its account was evicted along with it.
+    assert_eq!(
+        mempool.get_tx_status(input_address_0_nonce_1.tx.tx_hash),
+        Ok(TransactionStatus::Evicted)
+    );
+    let expected_queue_txs = [TransactionReference::new(&input_address_1_nonce_0.tx)];
+    let expected_pool_txs = [input_address_1_nonce_0.tx];
+    let expected_mempool_state = MempoolState::new(expected_pool_txs, expected_queue_txs);
+    expected_mempool_state.assert_eq_mempool_state(&mempool);
+}
+
+#[rstest]
+fn test_add_tx_evicts_expired_txs() {
+    // Setup.
+    let clock = Arc::new(FakeClock::default());
+    let tx_ttl = Duration::from_secs(60);
+    let mut mempool = Mempool::with_config_and_clock(
+        MempoolConfig { tx_ttl, ..Default::default() },
+        clock.clone(),
+    );
+    let input_expired = add_tx_input!(tip: 20, tx_hash: 1, sender_address: "0x0");
+    let input = add_tx_input!(tip: 10, tx_hash: 2, sender_address: "0x1");
+
+    add_tx(&mut mempool, &input_expired);
+    clock.advance(tx_ttl);
+
+    // Test.
+    add_tx(&mut mempool, &input);
+
+    // Assert.
+    assert_eq!(mempool.get_tx_status(input_expired.tx.tx_hash), Ok(TransactionStatus::Evicted));
+    let expected_queue_txs = [TransactionReference::new(&input.tx)];
+    let expected_mempool_state = MempoolState::new([input.tx], expected_queue_txs);
+    expected_mempool_state.assert_eq_mempool_state(&mempool);
+}
+
+#[rstest]
+fn test_get_txs_skips_expired_txs() {
+    // Setup.
+    let clock = Arc::new(FakeClock::default());
+    let tx_ttl = Duration::from_secs(60);
+    let mut mempool = Mempool::with_config_and_clock(
+        MempoolConfig { tx_ttl, ..Default::default() },
+        clock.clone(),
+    );
+    let input_expired = add_tx_input!(tip: 20, tx_hash: 1, sender_address: "0x0");
+    let input = add_tx_input!(tip: 10, tx_hash: 2, sender_address: "0x1");
+
+    add_tx(&mut mempool, &input_expired);
+    clock.advance(Duration::from_secs(30));
+    add_tx(&mut mempool, &input);
+    clock.advance(Duration::from_secs(30));
+
+    // Test.
+    let txs = mempool.get_txs(2).unwrap();
+
+    // Assert.
+    assert_eq!(txs, &[input.tx]);
+    let expected_mempool_state = MempoolState::new([], []);
+    expected_mempool_state.assert_eq_mempool_state(&mempool);
+}
+
+#[rstest]
+fn test_return_txs(mut mempool: Mempool) {
+    // Setup.
+    let input_tip_20 = add_tx_input!(tip: 20, tx_hash: 1, sender_address: "0x0");
+    let input_tip_10 = add_tx_input!(tip: 10, tx_hash: 2, sender_address: "0x1");
+
+    add_tx(&mut mempool, &input_tip_20);
+    add_tx(&mut mempool, &input_tip_10);
+    let txs = mempool.get_txs(2).unwrap();
+
+    // Test.
+    mempool.return_txs(txs).unwrap();
+
+    // Assert: the mempool is restored.
+    let expected_queue_txs =
+        [TransactionReference::new(&input_tip_10.tx), TransactionReference::new(&input_tip_20.tx)];
+    let expected_pool_txs = [input_tip_20.tx, input_tip_10.tx];
+    let expected_mempool_state = MempoolState::new(expected_pool_txs, expected_queue_txs);
+    expected_mempool_state.assert_eq_mempool_state(&mempool);
+}
+
+#[rstest]
+fn test_return_txs_rejects_unstaged_txs(mut mempool: Mempool) {
+    // Setup.
+    let input_staged = add_tx_input!(tip: 20, tx_hash: 1, sender_address: "0x0");
+    let input_queued = add_tx_input!(tip: 10, tx_hash: 2, sender_address: "0x1");
+
+    add_tx(&mut mempool, &input_staged);
+    add_tx(&mut mempool, &input_queued);
+    let txs = mempool.get_txs(1).unwrap();
+    assert_eq!(txs, vec![input_staged.tx.clone()]);
+
+    // Test and assert: the queued transaction was not retrieved, so none of the transactions are
+    // returned.
+    assert_eq!(
+        mempool.return_txs(vec![input_staged.tx.clone(), input_queued.tx.clone()]),
+        Err(MempoolError::TransactionNotFound { tx_hash: input_queued.tx.tx_hash })
+    );
+    assert_eq!(mempool.get_tx_status(input_staged.tx.tx_hash), Ok(TransactionStatus::Staged));
+    assert_eq!(mempool.check_invariants(), InvariantReport::default());
+
+    // A transaction cannot be returned twice.
+    assert_eq!(
+        mempool.return_txs(vec![input_staged.tx.clone(), input_staged.tx.clone()]),
+        Err(MempoolError::DuplicateTransaction { tx_hash: input_staged.tx.tx_hash })
+    );
+    assert_eq!(mempool.get_tx_status(input_staged.tx.tx_hash), Ok(TransactionStatus::Staged));
+}
+
+#[rstest]
+fn test_return_txs_rewinds_account_nonce(mut mempool: Mempool) {
+    // Setup.
+    let input_nonce_0 =
+        add_tx_input!(tx_hash: 1, sender_address: "0x0", tx_nonce: 0_u8, account_nonce: 0_u8);
+    let input_nonce_1 =
+        add_tx_input!(tx_hash: 2, sender_address: "0x0", tx_nonce: 1_u8, account_nonce: 0_u8);
+    let input_nonce_2 =
+        add_tx_input!(tx_hash: 3, sender_address: "0x0", tx_nonce: 2_u8, account_nonce: 0_u8);
+
+    for input in [&input_nonce_0, &input_nonce_1, &input_nonce_2] {
+        add_tx(&mut mempool, input);
+    }
+    let txs = mempool.get_txs(2).unwrap();
+    assert_eq!(txs, &[input_nonce_0.tx.clone(), input_nonce_1.tx.clone()]);
+
+    // Test: only the first transaction was included.
+    mempool.return_txs(vec![input_nonce_1.tx.clone()]).unwrap();
+
+    // Assert: the returned transaction replaces its successor in the queue.
+    let expected_queue_txs = [TransactionReference::new(&input_nonce_1.tx)];
+    let expected_pool_txs = [input_nonce_1.tx.clone(), input_nonce_2.tx.clone()];
+    let expected_mempool_state = MempoolState::new(expected_pool_txs, expected_queue_txs);
+    expected_mempool_state.assert_eq_mempool_state(&mempool);
+
+    let txs = mempool.get_txs(3).unwrap();
+    assert_eq!(txs, &[input_nonce_1.tx, input_nonce_2.tx]);
+}
+
+#[rstest]
+fn test_return_txs_rejects_partial_return(mut mempool: Mempool) {
+    // Setup.
+    let input_nonce_0 =
+        add_tx_input!(tx_hash: 1, sender_address: "0x0", tx_nonce: 0_u8, account_nonce: 0_u8);
+    let input_nonce_1 =
+        add_tx_input!(tx_hash: 2, sender_address: "0x0", tx_nonce: 1_u8, account_nonce: 0_u8);
+
+    for input in [&input_nonce_0, &input_nonce_1] {
+        add_tx(&mut mempool, input);
+    }
+    mempool.get_txs(2).unwrap();
+
+    // Test and assert: the staged transaction following the returned one could not be sequenced
+    // once the account is rewound, so none of the transactions are returned.
+    assert_eq!(
+        mempool.return_txs(vec![input_nonce_0.tx.clone()]),
+        Err(MempoolError::StagedTransactionNotReturned { tx_hash: input_nonce_1.tx.tx_hash })
+    );
+    for tx in [&input_nonce_0.tx, &input_nonce_1.tx] {
+        assert_eq!(mempool.get_tx_status(tx.tx_hash), Ok(TransactionStatus::Staged));
+    }
+
+    // Returning both transactions rewinds the account.
+    mempool.return_txs(vec![input_nonce_0.tx.clone(), input_nonce_1.tx.clone()]).unwrap();
+    let txs = mempool.get_txs(2).unwrap();
+    assert_eq!(txs, &[input_nonce_0.tx, input_nonce_1.tx]);
 }
 
 #[rstest]
@@ -435,10 +2191,16 @@ fn test_commit_block_rewinds_nonce() {
 
     // Test.
     let state_changes = HashMap::from([
-        (contract_address!("0x0"), AccountState { nonce: Nonce(felt!(3_u16)) }),
-        (contract_address!("0x1"), AccountState { nonce: Nonce(felt!(3_u16)) }),
+        (
+            contract_address!("0x0"),
+            AccountState { nonce: Nonce(felt!(3_u16)), ..Default::default() },
+        ),
+        (
+            contract_address!("0x1"),
+            AccountState { nonce: Nonce(felt!(3_u16)), ..Default::default() },
+        ),
     ]);
-    assert!(mempool.commit_block(state_changes).is_ok());
+    assert!(mempool.commit_block(CommitBlockArgs { state_changes, ..Default::default() }).is_ok());
 
     // Assert.
     assert_eq_mempool_queue(&mempool, &[])
@@ -0,0 +1,210 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::time::Duration;
+
+use starknet_mempool_types::errors::MempoolError;
+
+#[cfg(test)]
+#[path = "metrics_test.rs"]
+mod metrics_test;
+
+/// The upper bounds of the buckets of the `get_txs` chunk size histogram.
+const CHUNK_SIZE_BUCKETS: [f64; 9] = [0.0, 1.0, 5.0, 10.0, 50.0, 100.0, 500.0, 1000.0, 5000.0];
+
+/// The upper bounds of the buckets of the time-in-mempool histogram, in seconds.
+const TIME_IN_MEMPOOL_BUCKETS: [f64; 10] =
+    [0.1, 0.5, 1.0, 5.0, 10.0, 30.0, 60.0, 300.0, 900.0, 3600.0];
+
+/// Counters of the mempool's operations.
+#[derive(Debug)]
+pub struct MempoolMetrics {
+    added_txs: u64,
+    // Rejected transactions, by the name of the error they were rejected with.
+    rejected_txs: BTreeMap<&'static str, u64>,
+    get_txs_chunk_sizes: Histogram,
+}
+
+impl Default for MempoolMetrics {
+    fn default() -> Self {
+        MempoolMetrics {
+            added_txs: 0,
+            rejected_txs: BTreeMap::new(),
+            get_txs_chunk_sizes: Histogram::new(&CHUNK_SIZE_BUCKETS),
+        }
+    }
+}
+
+impl MempoolMetrics {
+    pub fn record_add_tx(&mut self, result: &Result<(), MempoolError>) {
+        match result {
+            Ok(()) => self.added_txs += 1,
+            Err(error) => *self.rejected_txs.entry(error_name(error)).or_default() += 1,
+        }
+    }
+
+    pub fn record_get_txs(&mut self, n_txs: usize) {
+        self.get_txs_chunk_sizes.observe(f64::from(u32::try_from(n_txs).unwrap_or(u32::MAX)));
+    }
+
+    pub fn encode(&self, encoder: &mut PrometheusEncoder) {
+        encoder.counter(
+            "mempool_added_txs_total",
+            "Number of transactions added to the mempool.",
+            self.added_txs,
+        );
+        encoder.labeled_counter(
+            "mempool_rejected_txs_total",
+            "Number of transactions rejected by the mempool, by rejection reason.",
+            "reason",
+            self.rejected_txs.iter().map(|(&reason, &count)| (reason, count)),
+        );
+        encoder.histogram(
+            "mempool_get_txs_chunk_size",
+            "Number of transactions retrieved from the mempool per request.",
+            &self.get_txs_chunk_sizes,
+        );
+    }
+}
+
+/// Tracks the time transactions spend in the transaction pool.
+#[derive(Debug)]
+pub struct TimeInMempoolMetric(Histogram);
+
+impl Default for TimeInMempoolMetric {
+    fn default() -> Self {
+        TimeInMempoolMetric(Histogram::new(&TIME_IN_MEMPOOL_BUCKETS))
+    }
+}
+
+impl TimeInMempoolMetric {
+    pub fn observe(&mut self, time_in_mempool: Duration) {
+        self.0.observe(time_in_mempool.as_secs_f64());
+    }
+
+    pub fn encode(&self, encoder: &mut PrometheusEncoder) {
+        encoder.histogram(
+            "mempool_time_in_mempool_seconds",
+            "Time transactions spent in the mempool until leaving it, for any reason.",
+            &self.0,
+        );
+    }
+}
+
+/// A histogram with fixed buckets, as defined by Prometheus.
+#[derive(Debug)]
+pub struct Histogram {
+    bucket_bounds: &'static [f64],
+    // The number of observations that fall in each bucket (non-cumulative).
+    bucket_counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    pub fn new(bucket_bounds: &'static [f64]) -> Self {
+        Histogram { bucket_bounds, bucket_counts: vec![0; bucket_bounds.len()], sum: 0.0, count: 0 }
+    }
+
+    pub fn observe(&mut self, value: f64) {
+        if let Some(bucket) = self.bucket_bounds.iter().position(|&bound| value <= bound) {
+            self.bucket_counts[bucket] += 1;
+        }
+        self.sum += value;
+        self.count += 1;
+    }
+}
+
+/// Encodes metrics in the Prometheus text exposition format.
+#[derive(Debug, Default)]
+pub struct PrometheusEncoder {
+    output: String,
+}
+
+impl PrometheusEncoder {
+    pub fn gauge(&mut self, name: &str, help: &str, value: u64) {
+        self.header(name, help, "gauge");
+        self.line(name, "", value);
+    }
+
+    pub fn counter(&mut self, name: &str, help: &str, value: u64) {
+        self.header(name, help, "counter");
+        self.line(name, "", value);
+    }
+
+    pub fn labeled_counter<'a>(
+        &mut self,
+        name: &str,
+        help: &str,
+        label: &str,
+        values: impl Iterator<Item = (&'a str, u64)>,
+    ) {
+        self.header(name, help, "counter");
+        for (label_value, value) in values {
+            let label_value = escape_label_value(label_value);
+            self.line(name, &format!("{{{label}=\"{label_value}\"}}"), value);
+        }
+    }
+
+    pub fn histogram(&mut self, name: &str, help: &str, histogram: &Histogram) {
+        self.header(name, help, "histogram");
+        let bucket_name = format!("{name}_bucket");
+        let mut cumulative_count = 0;
+        for (bound, count) in histogram.bucket_bounds.iter().zip(&histogram.bucket_counts) {
+            cumulative_count += count;
+            let bound = format_float(*bound);
+            self.line(&bucket_name, &format!("{{le=\"{bound}\"}}"), cumulative_count);
+        }
+        self.line(&bucket_name, "{le=\"+Inf\"}", histogram.count);
+        self.line(&format!("{name}_sum"), "", format_float(histogram.sum));
+        self.line(&format!("{name}_count"), "", histogram.count);
+    }
+
+    pub fn finish(self) -> String {
+        self.output
+    }
+
+    fn header(&mut self, name: &str, help: &str, metric_type: &str) {
+        // Writing to a string never fails.
+        let help = help.replace('\\', "\\\\").replace('\n', "\\n");
+        let _ = writeln!(self.output, "# HELP {name} {help}");
+        let _ = writeln!(self.output, "# TYPE {name} {metric_type}");
+    }
+
+    fn line(&mut self, name: &str, labels: &str, value: impl std::fmt::Display) {
+        let _ = writeln!(self.output, "{name}{labels} {value}");
+    }
+}
+
+/// Escapes a label value, as required by the exposition format.
+fn escape_label_value(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// Formats a floating-point value as in the exposition format, with whole values keeping their
+/// fractional part (e.g., "1.0" rather than "1"), and infinities as "+Inf" and "-Inf".
+fn format_float(value: f64) -> String {
+    if value.is_infinite() {
+        return if value > 0.0 { "+Inf".to_string() } else { "-Inf".to_string() };
+    }
+    if value.is_finite() && value.fract() == 0.0 {
+        return format!("{value:.1}");
+    }
+
+    value.to_string()
+}
+
+fn error_name(error: &MempoolError) -> &'static str {
+    match error {
+        MempoolError::DuplicateTransaction { .. } => "DuplicateTransaction",
+        MempoolError::InsufficientBalance { .. } => "InsufficientBalance",
+        MempoolError::InsufficientFeeEscalation { .. } => "InsufficientFeeEscalation",
+        MempoolError::MempoolFull { .. } => "MempoolFull",
+        MempoolError::MissingDeployAccount { .. } => "MissingDeployAccount",
+        MempoolError::NonceTooFarAhead { .. } => "NonceTooFarAhead",
+        MempoolError::NonceTooOld { .. } => "NonceTooOld",
+        MempoolError::StagedTransactionNotReturned { .. } => "StagedTransactionNotReturned",
+        MempoolError::TooManyAccountTransactions { .. } => "TooManyAccountTransactions",
+        MempoolError::TransactionNotFound { .. } => "TransactionNotFound",
+        MempoolError::FeltOutOfRange => "FeltOutOfRange",
+    }
+}
@@ -0,0 +1,43 @@
+use pretty_assertions::assert_eq;
+
+use crate::metrics::{Histogram, PrometheusEncoder};
+
+#[test]
+fn test_prometheus_encoder_output() {
+    // Setup.
+    let mut histogram = Histogram::new(&[0.5, 1.0, 10.0]);
+    for value in [0.25, 1.0, 20.0] {
+        histogram.observe(value);
+    }
+
+    // Test.
+    let mut encoder = PrometheusEncoder::default();
+    encoder.gauge("test_gauge", "A gauge.", 1);
+    encoder.labeled_counter(
+        "test_counter_total",
+        "A counter, with a \\ and a\nnew line.",
+        "reason",
+        [("plain", 2), ("quoted \"reason\"", 3), ("back\\slash\nnew line", 4)].into_iter(),
+    );
+    encoder.histogram("test_histogram", "A histogram.", &histogram);
+
+    // Assert.
+    let expected_output = r#"# HELP test_gauge A gauge.
+# TYPE test_gauge gauge
+test_gauge 1
+# HELP test_counter_total A counter, with a \\ and a\nnew line.
+# TYPE test_counter_total counter
+test_counter_total{reason="plain"} 2
+test_counter_total{reason="quoted \"reason\""} 3
+test_counter_total{reason="back\\slash\nnew line"} 4
+# HELP test_histogram A histogram.
+# TYPE test_histogram histogram
+test_histogram_bucket{le="0.5"} 1
+test_histogram_bucket{le="1.0"} 2
+test_histogram_bucket{le="10.0"} 2
+test_histogram_bucket{le="+Inf"} 3
+test_histogram_sum 21.25
+test_histogram_count 3
+"#;
+    assert_eq!(encoder.finish(), expected_output);
+}
@@ -0,0 +1,42 @@
+use std::fmt::Debug;
+
+use serde::{Deserialize, Serialize};
+
+use crate::mempool::TransactionReference;
+
+/// Determines the order in which eligible transactions are sequenced.
+pub trait TransactionOrdering: Debug + Send + Sync {
+    /// Returns the priority of the given transaction; transactions with a higher priority are
+    /// sequenced first, and ties are broken by the transaction hash.
+    fn priority(&self, tx: &TransactionReference) -> u128;
+}
+
+/// The built-in ordering policies, selectable through the mempool config.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum TransactionOrderingPolicy {
+    /// Orders by tip.
+    #[default]
+    Tip,
+    /// Orders by the maximal price per L2 gas unit the transaction is willing to pay.
+    EffectiveL2GasPrice,
+    /// Orders by tip, relative to the maximal amount of gas (L1 and L2) the transaction may
+    /// consume.
+    TipPerGas,
+    /// Orders by arrival to the mempool, earliest first.
+    Fifo,
+}
+
+impl TransactionOrdering for TransactionOrderingPolicy {
+    fn priority(&self, tx: &TransactionReference) -> u128 {
+        match self {
+            TransactionOrderingPolicy::Tip => tx.tip.0.into(),
+            TransactionOrderingPolicy::EffectiveL2GasPrice => tx.l2_gas.max_price_per_unit,
+            TransactionOrderingPolicy::TipPerGas => {
+                let max_gas_amount = tx.l1_gas.max_amount.saturating_add(tx.l2_gas.max_amount);
+                // The tip is scaled up to keep the precision of the division.
+                (u128::from(tx.tip.0) << 64) / u128::from(max_gas_amount.max(1))
+            }
+            TransactionOrderingPolicy::Fifo => u128::MAX - u128::from(tx.arrival),
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use std::fs::{self, File};
+use std::future::Future;
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use starknet_mempool_types::mempool_types::{Account, ThinTransaction};
+use tokio::time::{interval, MissedTickBehavior};
+use tracing::error;
+
+use crate::config::MempoolSnapshotConfig;
+use crate::mempool::Mempool;
+
+/// A serializable representation of the transactions held in the mempool, from which it can be
+/// restored (e.g., after a restart).
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct MempoolSnapshot {
+    // The transactions held in the mempool, ordered by their arrival.
+    pub txs: Vec<SnapshotTransaction>,
+    // The state of each account with transactions in the snapshot, as tracked by the mempool.
+    pub accounts: Vec<Account>,
+}
+
+/// A transaction held in the mempool, along with the sequence number of its arrival, which
+/// arrival-based orderings keep across restarts.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SnapshotTransaction {
+    pub tx: ThinTransaction,
+    pub arrival: u64,
+}
+
+impl MempoolSnapshot {
+    pub fn read_from_file(path: &Path) -> io::Result<Self> {
+        let reader = BufReader::new(File::open(path)?);
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    /// Writes the snapshot to the given file. The snapshot is first written to a temporary file,
+    /// which then replaces the given one, so that a crash never leaves a partially written
+    /// snapshot behind.
+    pub fn write_to_file(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent_dir) = path.parent() {
+            fs::create_dir_all(parent_dir)?;
+        }
+
+        let tmp_path = path.with_extension("tmp");
+        let mut writer = BufWriter::new(File::create(&tmp_path)?);
+        serde_json::to_writer(&mut writer, self)?;
+        writer.flush()?;
+        writer.get_ref().sync_all()?;
+
+        fs::rename(tmp_path, path)
+    }
+}
+
+/// Writes snapshots of the given mempool at the configured interval, and a final one once
+/// `shutdown` completes. The mempool is only locked while its snapshot is taken; the snapshot is
+/// written on a blocking thread, off the mempool's request path.
+pub async fn write_snapshots(
+    mempool: Arc<Mutex<Mempool>>,
+    config: MempoolSnapshotConfig,
+    shutdown: impl Future<Output = ()>,
+) {
+    let MempoolSnapshotConfig { path, flush_interval } = config;
+    let mut flush_interval = interval(flush_interval);
+    flush_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    // The first tick completes immediately.
+    flush_interval.tick().await;
+    tokio::pin!(shutdown);
+
+    loop {
+        tokio::select! {
+            _ = flush_interval.tick() => write_snapshot(&mempool, path.clone()).await,
+            () = &mut shutdown => {
+                write_snapshot(&mempool, path).await;
+                return;
+            }
+        }
+    }
+}
+
+async fn write_snapshot(mempool: &Mutex<Mempool>, path: PathBuf) {
+    let snapshot = mempool.lock().expect("The mempool lock should not be poisoned.").snapshot();
+    let result = tokio::task::spawn_blocking(move || {
+        snapshot.write_to_file(&path).map_err(|error| (path, error))
+    })
+    .await;
+
+    match result {
+        Ok(Ok(())) => {}
+        Ok(Err((path, error))) => {
+            error!("Failed to write a mempool snapshot to {}: {error}", path.display());
+        }
+        Err(error) => error!("Failed to write a mempool snapshot: {error}"),
+    }
+}
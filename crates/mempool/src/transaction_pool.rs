@@ -1,33 +1,72 @@
-use std::collections::{hash_map, BTreeMap, HashMap};
+use std::collections::{hash_map, BTreeMap, BTreeSet, HashMap};
+use std::sync::Arc;
+use std::time::Instant;
 
 use starknet_api::core::{ContractAddress, Nonce};
 use starknet_api::transaction::TransactionHash;
 use starknet_mempool_types::errors::MempoolError;
 use starknet_mempool_types::mempool_types::{MempoolResult, ThinTransaction};
 
+use crate::invariants::{InvariantReport, InvariantViolation};
 use crate::mempool::TransactionReference;
+use crate::metrics::{PrometheusEncoder, TimeInMempoolMetric};
+use crate::utils::{Clock, InstantClock};
 
 type HashToTransaction = HashMap<TransactionHash, ThinTransaction>;
 
 /// Contains all transactions currently held in the mempool.
-/// Invariant: both data structures are consistent regarding the existence of transactions:
-/// A transaction appears in one if and only if it appears in the other.
+/// Invariant: all data structures are consistent regarding the existence of transactions:
+/// A transaction appears in one if and only if it appears in the others.
 /// No duplicate transactions appear in the pool.
-#[derive(Debug, Default, Eq, PartialEq)]
+#[derive(Debug)]
 pub struct TransactionPool {
     // Holds the complete transaction objects; it should be the sole entity that does so.
     tx_pool: HashToTransaction,
     // Transactions organized by account address, sorted by ascending nonce values.
     txs_by_account: AccountTransactionIndex,
+    // Transactions organized by their insertion time.
+    txs_by_insertion_time: TimedTransactionIndex,
+    // The total estimated size of the transactions in the pool, in bytes.
+    size_in_bytes: usize,
+    // Counts the transactions that arrived to the pool, for ordering them by arrival.
+    n_arrivals: u64,
+    time_in_pool: TimeInMempoolMetric,
+    clock: Arc<dyn Clock>,
 }
 
 impl TransactionPool {
-    pub fn insert(&mut self, tx: ThinTransaction) -> MempoolResult<()> {
-        let tx_reference = TransactionReference::new(&tx);
+    pub fn new(clock: Arc<dyn Clock>) -> Self {
+        TransactionPool {
+            tx_pool: HashToTransaction::default(),
+            txs_by_account: AccountTransactionIndex::default(),
+            txs_by_insertion_time: TimedTransactionIndex::default(),
+            size_in_bytes: 0,
+            n_arrivals: 0,
+            time_in_pool: TimeInMempoolMetric::default(),
+            clock,
+        }
+    }
+
+    /// Inserts a transaction that arrived to the mempool, assigning it the next arrival sequence
+    /// number, and returns its reference.
+    pub fn insert(&mut self, tx: ThinTransaction) -> MempoolResult<TransactionReference> {
+        self.reinsert(tx, self.n_arrivals)
+    }
+
+    /// Inserts a transaction that was previously removed from the pool (e.g., returned after being
+    /// handed out for sequencing, or restored from a snapshot), keeping its original arrival
+    /// sequence number. Later arrivals are numbered after it.
+    pub fn reinsert(
+        &mut self,
+        tx: ThinTransaction,
+        arrival: u64,
+    ) -> MempoolResult<TransactionReference> {
+        let tx_reference = TransactionReference { arrival, ..TransactionReference::new(&tx) };
         let tx_hash = tx_reference.tx_hash;
 
         // Insert to pool.
         if let hash_map::Entry::Vacant(entry) = self.tx_pool.entry(tx_hash) {
+            self.size_in_bytes += estimated_tx_size(&tx);
             entry.insert(tx);
         } else {
             return Err(MempoolError::DuplicateTransaction { tx_hash });
@@ -41,8 +80,10 @@ impl TransactionPool {
                  appear in main mapping, but it appears in the account mapping",
             )
         };
+        self.txs_by_insertion_time.insert(tx_hash, self.clock.now());
+        self.n_arrivals = self.n_arrivals.max(arrival.saturating_add(1));
 
-        Ok(())
+        Ok(tx_reference)
     }
 
     pub fn remove(&mut self, tx_hash: TransactionHash) -> MempoolResult<ThinTransaction> {
@@ -57,27 +98,45 @@ impl TransactionPool {
                  main mapping, but does not appear in the account mapping"
             )
         });
+        self.remove_insertion_time(tx_hash);
+        self.size_in_bytes -= estimated_tx_size(&tx);
 
         Ok(tx)
     }
 
-    pub fn remove_up_to_nonce(&mut self, address: ContractAddress, nonce: Nonce) {
+    /// Removes the transactions of the given account with a nonce lower than the given one, and
+    /// returns their hashes.
+    pub fn remove_up_to_nonce(
+        &mut self,
+        address: ContractAddress,
+        nonce: Nonce,
+    ) -> Vec<TransactionHash> {
         let removed_txs = self.txs_by_account.remove_up_to_nonce(address, nonce);
+        let mut removed_tx_hashes = Vec::with_capacity(removed_txs.len());
 
         for TransactionReference { tx_hash, .. } in removed_txs {
-            self.tx_pool.remove(&tx_hash).unwrap_or_else(|| {
+            let tx = self.tx_pool.remove(&tx_hash).unwrap_or_else(|| {
                 panic!(
                     "Transaction pool consistency error: transaction with hash {tx_hash} appears \
                      in account mapping, but does not appear in the main mapping"
                 );
             });
+            self.remove_insertion_time(tx_hash);
+            self.size_in_bytes -= estimated_tx_size(&tx);
+            removed_tx_hashes.push(tx_hash);
         }
+
+        removed_tx_hashes
     }
 
     pub fn get_by_tx_hash(&self, tx_hash: TransactionHash) -> MempoolResult<&ThinTransaction> {
         self.tx_pool.get(&tx_hash).ok_or(MempoolError::TransactionNotFound { tx_hash })
     }
 
+    pub fn contains(&self, tx_hash: TransactionHash) -> bool {
+        self.tx_pool.contains_key(&tx_hash)
+    }
+
     pub fn get_by_address_and_nonce(
         &self,
         address: ContractAddress,
@@ -86,12 +145,190 @@ impl TransactionPool {
         self.txs_by_account.get(address, nonce)
     }
 
+    /// Returns the number of transactions of the given account in the pool.
+    pub fn account_txs_count(&self, address: ContractAddress) -> usize {
+        self.txs_by_account.account_txs_count(address)
+    }
+
+    /// Returns the hashes of the transactions of the given account in the pool with a nonce at
+    /// least the given one, ordered by nonce.
+    pub fn account_tx_hashes_from_nonce(
+        &self,
+        address: ContractAddress,
+        nonce: Nonce,
+    ) -> Vec<TransactionHash> {
+        self.txs_by_account.account_txs_from_nonce(address, nonce).map(|tx| tx.tx_hash).collect()
+    }
+
+    /// Returns the transaction of the given account with the lowest nonce, if any.
+    pub fn get_first_tx(&self, address: ContractAddress) -> Option<&TransactionReference> {
+        self.txs_by_account.account_txs_from_nonce(address, Nonce::default()).next()
+    }
+
+    /// Returns the transaction of the given account preceding the given nonce, if any.
+    pub fn get_preceding_tx(
+        &self,
+        address: ContractAddress,
+        nonce: Nonce,
+    ) -> Option<&TransactionReference> {
+        self.txs_by_account.get_preceding(address, nonce)
+    }
+
+    /// Returns an iterator of the highest-nonce transaction of each account.
+    pub fn last_txs_by_account(&self) -> impl Iterator<Item = &TransactionReference> {
+        self.txs_by_account.last_txs()
+    }
+
+    /// Returns the hashes of the transactions inserted at or before the given time, oldest first.
+    pub fn get_inserted_until(&self, time: Instant) -> Vec<TransactionHash> {
+        self.txs_by_insertion_time.get_inserted_until(time)
+    }
+
+    /// Returns an iterator of the transactions in the pool, ordered by their insertion time.
+    pub fn iter_by_insertion_time(&self) -> impl Iterator<Item = &ThinTransaction> {
+        self.txs_by_insertion_time.iter().map(|tx_hash| {
+            self.tx_pool.get(&tx_hash).unwrap_or_else(|| {
+                panic!(
+                    "Transaction pool consistency error: transaction with hash {tx_hash} appears \
+                     in insertion time mapping, but does not appear in the main mapping"
+                )
+            })
+        })
+    }
+
+    /// Returns the transactions in the pool along with their arrival sequence numbers, ordered by
+    /// arrival.
+    pub fn txs_by_arrival(&self) -> Vec<(&ThinTransaction, u64)> {
+        let mut txs: Vec<(&ThinTransaction, u64)> = self
+            .txs_by_account
+            .0
+            .values()
+            .flat_map(BTreeMap::values)
+            .map(|&TransactionReference { tx_hash, arrival, .. }| {
+                let tx = self.tx_pool.get(&tx_hash).unwrap_or_else(|| {
+                    panic!(
+                        "Transaction pool consistency error: transaction with hash {tx_hash} \
+                         appears in account mapping, but does not appear in the main mapping"
+                    )
+                });
+                (tx, arrival)
+            })
+            .collect();
+        txs.sort_unstable_by_key(|&(_, arrival)| arrival);
+
+        txs
+    }
+
+    pub fn len(&self) -> usize {
+        self.tx_pool.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tx_pool.is_empty()
+    }
+
+    pub fn size_in_bytes(&self) -> usize {
+        self.size_in_bytes
+    }
+
+    pub fn encode_metrics(&self, encoder: &mut PrometheusEncoder) {
+        encoder.gauge(
+            "mempool_pool_size",
+            "Number of transactions held in the mempool.",
+            self.len().try_into().unwrap_or(u64::MAX),
+        );
+        encoder.gauge(
+            "mempool_pool_size_bytes",
+            "Estimated total size of the transactions held in the mempool, in bytes.",
+            self.size_in_bytes.try_into().unwrap_or(u64::MAX),
+        );
+        encoder.gauge(
+            "mempool_accounts",
+            "Number of accounts with transactions held in the mempool.",
+            self.txs_by_account.n_accounts().try_into().unwrap_or(u64::MAX),
+        );
+        self.time_in_pool.encode(encoder);
+    }
+
+    /// Checks the consistency of the pool's data structures.
+    pub fn check_invariants(&self, report: &mut InvariantReport) {
+        for (&tx_hash, tx) in &self.tx_pool {
+            if self.txs_by_account.get(tx.sender_address, tx.nonce)
+                != Some(&TransactionReference::new(tx))
+            {
+                report.report(InvariantViolation::MissingFromAccountIndex { tx_hash });
+            }
+        }
+
+        for (address, account_txs) in &self.txs_by_account.0 {
+            for (nonce, tx) in account_txs {
+                if !self.tx_pool.contains_key(&tx.tx_hash) {
+                    report
+                        .report(InvariantViolation::MissingFromMainMapping { tx_hash: tx.tx_hash });
+                }
+                if tx.sender_address != *address || tx.nonce != *nonce {
+                    report.report(InvariantViolation::MisplacedInAccountIndex {
+                        tx_hash: tx.tx_hash,
+                    });
+                }
+            }
+        }
+
+        let TimedTransactionIndex { txs_by_insertion_time, insertion_times } =
+            &self.txs_by_insertion_time;
+        let n_indexed_txs = txs_by_insertion_time.len();
+        let is_indexed = |tx_hash| insertion_times.contains_key(tx_hash);
+        if n_indexed_txs != self.len()
+            || insertion_times.len() != self.len()
+            || !self.tx_pool.keys().all(is_indexed)
+        {
+            report.report(InvariantViolation::InsertionTimeIndexMismatch {
+                n_indexed_txs,
+                n_txs: self.len(),
+            });
+        }
+
+        let size_in_bytes = self.tx_pool.values().map(estimated_tx_size).sum();
+        if self.size_in_bytes != size_in_bytes {
+            report.report(InvariantViolation::SizeMismatch {
+                tracked_size_in_bytes: self.size_in_bytes,
+                size_in_bytes,
+            });
+        }
+    }
+
+    /// Removes the insertion time of a removed transaction, recording the time it spent in the
+    /// pool.
+    fn remove_insertion_time(&mut self, tx_hash: TransactionHash) {
+        if let Some(insertion_time) = self.txs_by_insertion_time.remove(tx_hash) {
+            self.time_in_pool.observe(self.clock.now().saturating_duration_since(insertion_time));
+        }
+    }
+
     #[cfg(test)]
     pub(crate) fn _tx_pool(&self) -> &HashToTransaction {
         &self.tx_pool
     }
 }
 
+impl Default for TransactionPool {
+    fn default() -> Self {
+        TransactionPool::new(Arc::new(InstantClock))
+    }
+}
+
+// Note: insertion times and the arrival counter are not compared, as they depend on the insertion
+// history.
+impl PartialEq for TransactionPool {
+    fn eq(&self, other: &Self) -> bool {
+        self.tx_pool == other.tx_pool
+            && self.txs_by_account == other.txs_by_account
+            && self.size_in_bytes == other.size_in_bytes
+    }
+}
+
+impl Eq for TransactionPool {}
+
 #[derive(Debug, Default, Eq, PartialEq)]
 struct AccountTransactionIndex(HashMap<ContractAddress, BTreeMap<Nonce, TransactionReference>>);
 
@@ -118,6 +355,38 @@ impl AccountTransactionIndex {
         self.0.get(&address)?.get(&nonce)
     }
 
+    fn n_accounts(&self) -> usize {
+        self.0.len()
+    }
+
+    fn account_txs_count(&self, address: ContractAddress) -> usize {
+        self.0.get(&address).map_or(0, BTreeMap::len)
+    }
+
+    fn account_txs_from_nonce(
+        &self,
+        address: ContractAddress,
+        nonce: Nonce,
+    ) -> impl Iterator<Item = &TransactionReference> {
+        self.0
+            .get(&address)
+            .into_iter()
+            .flat_map(move |account_txs| account_txs.range(nonce..))
+            .map(|(_, tx)| tx)
+    }
+
+    fn get_preceding(
+        &self,
+        address: ContractAddress,
+        nonce: Nonce,
+    ) -> Option<&TransactionReference> {
+        self.0.get(&address)?.range(..nonce).next_back().map(|(_, tx)| tx)
+    }
+
+    fn last_txs(&self) -> impl Iterator<Item = &TransactionReference> {
+        self.0.values().filter_map(|account_txs| account_txs.values().next_back())
+    }
+
     fn remove_up_to_nonce(
         &mut self,
         address: ContractAddress,
@@ -139,3 +408,40 @@ impl AccountTransactionIndex {
         txs_with_lower_nonce.into_values().collect()
     }
 }
+
+#[derive(Debug, Default, Eq, PartialEq)]
+struct TimedTransactionIndex {
+    txs_by_insertion_time: BTreeSet<(Instant, TransactionHash)>,
+    insertion_times: HashMap<TransactionHash, Instant>,
+}
+
+impl TimedTransactionIndex {
+    fn insert(&mut self, tx_hash: TransactionHash, insertion_time: Instant) {
+        self.txs_by_insertion_time.insert((insertion_time, tx_hash));
+        self.insertion_times.insert(tx_hash, insertion_time);
+    }
+
+    /// Returns the insertion time of the removed transaction, if it was present.
+    fn remove(&mut self, tx_hash: TransactionHash) -> Option<Instant> {
+        let insertion_time = self.insertion_times.remove(&tx_hash)?;
+        self.txs_by_insertion_time.remove(&(insertion_time, tx_hash));
+        Some(insertion_time)
+    }
+
+    fn iter(&self) -> impl Iterator<Item = TransactionHash> + '_ {
+        self.txs_by_insertion_time.iter().map(|(_, tx_hash)| *tx_hash)
+    }
+
+    fn get_inserted_until(&self, time: Instant) -> Vec<TransactionHash> {
+        self.txs_by_insertion_time
+            .iter()
+            .take_while(|(insertion_time, _)| *insertion_time <= time)
+            .map(|(_, tx_hash)| *tx_hash)
+            .collect()
+    }
+}
+
+/// Estimates the size of the given transaction, as held in the pool, in bytes.
+pub fn estimated_tx_size(tx: &ThinTransaction) -> usize {
+    std::mem::size_of::<ThinTransaction>() + tx.executable_tx.estimated_size()
+}
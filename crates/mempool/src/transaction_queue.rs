@@ -2,53 +2,128 @@ use std::cmp::Ordering;
 use std::collections::{BTreeSet, HashMap};
 
 use starknet_api::core::{ContractAddress, Nonce};
-use starknet_api::transaction::TransactionHash;
 
+use crate::config::FairnessConfig;
+use crate::invariants::{InvariantReport, InvariantViolation};
 use crate::mempool::TransactionReference;
+use crate::metrics::PrometheusEncoder;
+use crate::ordering::{TransactionOrdering, TransactionOrderingPolicy};
 
-// Note: the derived comparison functionality considers the order guaranteed by the data structures
-// used.
 #[derive(Debug, Default, Eq, PartialEq)]
-pub struct TransactionQueue {
+pub struct TransactionQueue<O: TransactionOrdering = TransactionOrderingPolicy> {
     // Priority queue of transactions with associated priority.
     queue: BTreeSet<QueuedTransaction>,
     // Set of account addresses for efficient existence checks.
-    address_to_tx: HashMap<ContractAddress, TransactionReference>,
+    address_to_tx: HashMap<ContractAddress, QueuedTransaction>,
+    // Determines the priority of inserted transactions.
+    ordering: O,
+    // Limits the transactions of each account popped per retrieval, and breaks priority ties by
+    // arrival; disabled if not provided.
+    fairness: Option<FairnessConfig>,
 }
 
-impl TransactionQueue {
+impl<O: TransactionOrdering> TransactionQueue<O> {
+    pub fn new(ordering: O, fairness: Option<FairnessConfig>) -> Self {
+        TransactionQueue {
+            queue: BTreeSet::new(),
+            address_to_tx: HashMap::new(),
+            ordering,
+            fairness,
+        }
+    }
+
     /// Adds a transaction to the mempool, ensuring unique keys.
     /// Panics: if given a duplicate tx.
     // TODO(Mohammad): Add test for two transactions from the same address, expecting specific
     // assert.
     pub fn insert(&mut self, tx: TransactionReference) {
+        let priority = self.ordering.priority(&tx);
+        // In fairness mode, ties are broken by arrival to the mempool, earliest first.
+        let arrival_rank = if self.fairness.is_some() { u64::MAX - tx.arrival } else { 0 };
+        let queued_tx = QueuedTransaction { priority, arrival_rank, tx };
         assert_eq!(
-            self.address_to_tx.insert(tx.sender_address, tx),
+            self.address_to_tx.insert(tx.sender_address, queued_tx.clone()),
             None,
             "Only a single transaction from the same contract class can be in the mempool at a \
              time."
         );
         assert!(
-            self.queue.insert(tx.into()),
+            self.queue.insert(queued_tx),
             "Keys should be unique; duplicates are checked prior."
         );
     }
 
-    // TODO(gilad): remove collect
-    pub fn pop_chunk(&mut self, n_txs: usize) -> Vec<TransactionHash> {
-        let txs: Vec<TransactionReference> =
-            (0..n_txs).filter_map(|_| self.queue.pop_last().map(|tx| tx.0)).collect();
-        for tx in &txs {
+    /// Pops up to `n_txs` transactions with the highest priority.
+    /// In fairness mode, transactions of accounts that already contributed the maximal number of
+    /// transactions to the current retrieval, as counted by `account_tx_counts`, are moved to
+    /// `deferred_txs` rather than popped, so that they are not scanned again during the retrieval;
+    /// the caller is responsible for reinserting them once the retrieval ends. The counts are
+    /// updated with the popped transactions.
+    pub fn pop_chunk(
+        &mut self,
+        n_txs: usize,
+        account_tx_counts: &mut HashMap<ContractAddress, usize>,
+        deferred_txs: &mut Vec<TransactionReference>,
+    ) -> Vec<TransactionReference> {
+        let max_txs_per_account =
+            self.fairness.map_or(usize::MAX, |fairness| fairness.max_txs_per_account);
+        let mut txs = Vec::new();
+
+        while txs.len() < n_txs {
+            let Some(QueuedTransaction { tx, .. }) = self.queue.pop_last() else {
+                break;
+            };
             self.address_to_tx.remove(&tx.sender_address);
+
+            let account_tx_count = account_tx_counts.entry(tx.sender_address).or_default();
+            if *account_tx_count >= max_txs_per_account {
+                deferred_txs.push(tx);
+                continue;
+            }
+            *account_tx_count += 1;
+            txs.push(tx);
         }
 
-        txs.into_iter().map(|tx| tx.tx_hash).collect()
+        txs
     }
 
     /// Returns an iterator of the current eligible transactions for sequencing, ordered by their
     /// priority.
     pub fn iter(&self) -> impl Iterator<Item = &TransactionReference> {
-        self.queue.iter().rev().map(|tx| &tx.0)
+        self.queue.iter().rev().map(|tx| &tx.tx)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Checks that the queue holds exactly the transactions of its account mapping, a single one
+    /// per account.
+    pub fn check_invariants(&self, report: &mut InvariantReport) {
+        for (&address, tx) in &self.address_to_tx {
+            if tx.sender_address != address || !self.queue.contains(tx) {
+                report.report(InvariantViolation::QueueIndexMismatch { address });
+            }
+        }
+
+        for tx in &self.queue {
+            if self.address_to_tx.get(&tx.sender_address) != Some(tx) {
+                report
+                    .report(InvariantViolation::QueueIndexMismatch { address: tx.sender_address });
+            }
+        }
+    }
+
+    pub fn encode_metrics(&self, encoder: &mut PrometheusEncoder) {
+        encoder.gauge(
+            "mempool_queue_size",
+            "Number of transactions eligible for sequencing.",
+            self.len().try_into().unwrap_or(u64::MAX),
+        );
     }
 
     pub fn get_nonce(&self, address: ContractAddress) -> Option<Nonce> {
@@ -59,21 +134,30 @@ impl TransactionQueue {
     /// This is well-defined, since there is at most one transaction per address in the queue.
     pub fn remove(&mut self, address: ContractAddress) -> bool {
         if let Some(tx) = self.address_to_tx.remove(&address) {
-            return self.queue.remove(&tx.into());
+            return self.queue.remove(&tx);
         }
         false
     }
 }
 
 /// Encapsulates a transaction reference to assess its order (i.e., priority).
-#[derive(Clone, Debug, derive_more::Deref, derive_more::From)]
-struct QueuedTransaction(pub TransactionReference);
+#[derive(Clone, Debug, derive_more::Deref)]
+struct QueuedTransaction {
+    priority: u128,
+    // Breaks priority ties before the transaction hash; higher for earlier arrivals to the mempool
+    // in fairness mode, and zero otherwise.
+    arrival_rank: u64,
+    #[deref]
+    tx: TransactionReference,
+}
 
-/// Compare transactions based only on their tip, a uint, using the Eq trait. It ensures that two
-/// tips are either exactly equal or not.
+/// Compare transactions based only on their priority, a uint, using the Eq trait. It ensures that
+/// two priorities are either exactly equal or not.
 impl PartialEq for QueuedTransaction {
     fn eq(&self, other: &QueuedTransaction) -> bool {
-        self.tip == other.tip && self.tx_hash == other.tx_hash
+        self.priority == other.priority
+            && self.arrival_rank == other.arrival_rank
+            && self.tx_hash == other.tx_hash
     }
 }
 
@@ -84,7 +168,10 @@ impl Eq for QueuedTransaction {}
 
 impl Ord for QueuedTransaction {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.tip.cmp(&other.tip).then_with(|| self.tx_hash.cmp(&other.tx_hash))
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| self.arrival_rank.cmp(&other.arrival_rank))
+            .then_with(|| self.tx_hash.cmp(&other.tx_hash))
     }
 }
 
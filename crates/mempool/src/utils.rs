@@ -0,0 +1,18 @@
+use std::fmt::Debug;
+use std::time::Instant;
+
+/// A source of the current time, injectable to allow deterministic testing of time-dependent
+/// behavior.
+pub trait Clock: Debug + Send + Sync {
+    fn now(&self) -> Instant;
+}
+
+/// A clock reading the system's monotonic time.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct InstantClock;
+
+impl Clock for InstantClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
@@ -1,5 +1,8 @@
 use starknet_gateway::gateway::{create_gateway, Gateway};
+use starknet_mempool::config::MempoolConfig;
 use starknet_mempool::mempool::Mempool;
+use starknet_mempool::snapshot::MempoolSnapshot;
+use tracing::{error, info};
 
 use crate::communication::MempoolNodeClients;
 use crate::config::MempoolNodeConfig;
@@ -23,7 +26,42 @@ pub fn create_components(config: &MempoolNodeConfig, clients: &MempoolNodeClient
         None
     };
 
-    let mempool = if config.components.mempool.execute { Some(Mempool::empty()) } else { None };
+    let mempool = if config.components.mempool.execute {
+        Some(create_mempool(config.mempool_config.clone()))
+    } else {
+        None
+    };
 
     Components { gateway, mempool }
 }
+
+/// Creates the mempool, restoring it from its latest snapshot if snapshots are enabled and one
+/// exists.
+fn create_mempool(config: MempoolConfig) -> Mempool {
+    let Some(snapshot_path) = config.snapshot_config.as_ref().map(|config| config.path.clone())
+    else {
+        return Mempool::with_config(config);
+    };
+    if !snapshot_path.exists() {
+        return Mempool::with_config(config);
+    }
+
+    match MempoolSnapshot::read_from_file(&snapshot_path) {
+        Ok(snapshot) => {
+            info!(
+                "Restoring the mempool from {} ({} transactions).",
+                snapshot_path.display(),
+                snapshot.txs.len()
+            );
+            Mempool::restore(config, snapshot)
+        }
+        Err(error) => {
+            error!(
+                "Failed to read the mempool snapshot from {}, starting with an empty mempool: \
+                 {error}",
+                snapshot_path.display()
+            );
+            Mempool::with_config(config)
+        }
+    }
+}
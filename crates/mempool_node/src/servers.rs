@@ -1,11 +1,13 @@
 use std::future::pending;
 use std::pin::Pin;
+use std::sync::{Arc, Mutex};
 
 use futures::{Future, FutureExt};
 use starknet_gateway::communication::{create_gateway_server, GatewayServer};
 use starknet_mempool::communication::{create_mempool_server, MempoolServer};
+use starknet_mempool::snapshot::write_snapshots;
 use starknet_mempool_infra::component_server::definitions::ComponentServerStarter;
-use tracing::error;
+use tracing::{error, info};
 
 use crate::communication::MempoolNodeCommunication;
 use crate::components::Components;
@@ -14,6 +16,9 @@ use crate::config::MempoolNodeConfig;
 pub struct Servers {
     pub gateway: Option<Box<GatewayServer>>,
     pub mempool: Option<Box<MempoolServer>>,
+    // Writes the mempool's snapshots, if enabled; completes after writing a final snapshot on
+    // shutdown.
+    pub mempool_snapshot_writer: Option<Pin<Box<dyn Future<Output = ()> + Send>>>,
 }
 
 pub fn create_servers(
@@ -29,16 +34,20 @@ pub fn create_servers(
         None
     };
 
-    let mempool_server = if config.components.mempool.execute {
-        Some(Box::new(create_mempool_server(
-            components.mempool.expect("Mempool is not initialized."),
-            communication.take_mempool_rx(),
-        )))
+    let (mempool_server, mempool_snapshot_writer) = if config.components.mempool.execute {
+        let mempool =
+            Arc::new(Mutex::new(components.mempool.expect("Mempool is not initialized.")));
+        let snapshot_writer =
+            config.mempool_config.snapshot_config.clone().map(|snapshot_config| {
+                write_snapshots(mempool.clone(), snapshot_config, shutdown_signal()).boxed()
+            });
+        let server = create_mempool_server(mempool, communication.take_mempool_rx());
+        (Some(Box::new(server)), snapshot_writer)
     } else {
-        None
+        (None, None)
     };
 
-    Servers { gateway: gateway_server, mempool: mempool_server }
+    Servers { gateway: gateway_server, mempool: mempool_server, mempool_snapshot_writer }
 }
 
 pub async fn run_component_servers(
@@ -53,9 +62,14 @@ pub async fn run_component_servers(
     let mempool_future =
         get_server_future("Mempool", config.components.mempool.execute, servers.mempool);
 
+    // Mempool snapshot writer.
+    let mempool_snapshot_writer_future =
+        servers.mempool_snapshot_writer.unwrap_or_else(|| pending().boxed());
+
     // Start servers.
     let gateway_handle = tokio::spawn(gateway_future);
     let mempool_handle = tokio::spawn(mempool_future);
+    let mempool_snapshot_writer_handle = tokio::spawn(mempool_snapshot_writer_future);
 
     tokio::select! {
         res = gateway_handle => {
@@ -66,6 +80,11 @@ pub async fn run_component_servers(
             error!("Mempool Server stopped.");
             res?
         }
+        res = mempool_snapshot_writer_handle => {
+            // The writer only completes on shutdown, after writing a final snapshot.
+            info!("Shutting down.");
+            return Ok(res?);
+        }
     };
     error!("Servers ended with unexpected Ok.");
 
@@ -89,3 +108,11 @@ pub fn get_server_future(
     };
     server_future
 }
+
+/// Completes once the node is requested to shut down (i.e., on Ctrl-C).
+async fn shutdown_signal() {
+    if let Err(error) = tokio::signal::ctrl_c().await {
+        error!("Failed to listen for the shutdown signal: {error}");
+        pending::<()>().await;
+    }
+}
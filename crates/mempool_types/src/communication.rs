@@ -3,13 +3,17 @@ use std::sync::Arc;
 use async_trait::async_trait;
 use mockall::predicate::*;
 use mockall::*;
+use starknet_api::transaction::TransactionHash;
 use starknet_mempool_infra::component_client::definitions::ClientError;
 use starknet_mempool_infra::component_client::local_component_client::LocalComponentClient;
 use starknet_mempool_infra::component_definitions::ComponentRequestAndResponseSender;
 use thiserror::Error;
+use tokio::sync::broadcast;
 
 use crate::errors::MempoolError;
-use crate::mempool_types::{MempoolInput, ThinTransaction};
+use crate::mempool_types::{
+    CommitBlockArgs, MempoolEvent, MempoolInput, ResourceBudget, ThinTransaction, TransactionStatus,
+};
 
 pub type MempoolClientImpl = LocalComponentClient<MempoolRequest, MempoolResponse>;
 pub type MempoolResult<T> = Result<T, MempoolError>;
@@ -25,18 +29,48 @@ pub type SharedMempoolClient = Arc<dyn MempoolClient>;
 pub trait MempoolClient: Send + Sync {
     async fn add_tx(&self, mempool_input: MempoolInput) -> MempoolClientResult<()>;
     async fn get_txs(&self, n_txs: usize) -> MempoolClientResult<Vec<ThinTransaction>>;
+    async fn get_txs_with_budget(
+        &self,
+        budget: ResourceBudget,
+    ) -> MempoolClientResult<Vec<ThinTransaction>>;
+    async fn return_txs(&self, txs: Vec<ThinTransaction>) -> MempoolClientResult<()>;
+    async fn commit_block(&self, args: CommitBlockArgs) -> MempoolClientResult<()>;
+    async fn get_tx_status(
+        &self,
+        tx_hash: TransactionHash,
+    ) -> MempoolClientResult<TransactionStatus>;
+    /// Returns a receiver of the mempool's events, starting from the time of the call.
+    async fn subscribe(&self) -> MempoolClientResult<broadcast::Receiver<MempoolEvent>>;
+    /// Returns the mempool's metrics, in the Prometheus text exposition format.
+    async fn get_metrics(&self) -> MempoolClientResult<String>;
+    /// Succeeds if the mempool is serving requests.
+    async fn is_alive(&self) -> MempoolClientResult<()>;
 }
 
 #[derive(Debug)]
 pub enum MempoolRequest {
     AddTransaction(MempoolInput),
     GetTransactions(usize),
+    GetTransactionsWithBudget(ResourceBudget),
+    ReturnTransactions(Vec<ThinTransaction>),
+    CommitBlock(CommitBlockArgs),
+    GetTransactionStatus(TransactionHash),
+    Subscribe,
+    GetMetrics,
+    IsAlive,
 }
 
 #[derive(Debug)]
 pub enum MempoolResponse {
     AddTransaction(MempoolResult<()>),
     GetTransactions(MempoolResult<Vec<ThinTransaction>>),
+    GetTransactionsWithBudget(MempoolResult<Vec<ThinTransaction>>),
+    ReturnTransactions(MempoolResult<()>),
+    CommitBlock(MempoolResult<()>),
+    GetTransactionStatus(MempoolResult<TransactionStatus>),
+    Subscribe(MempoolResult<broadcast::Receiver<MempoolEvent>>),
+    GetMetrics(MempoolResult<String>),
+    IsAlive(MempoolResult<()>),
 }
 
 #[derive(Debug, Error)]
@@ -72,4 +106,94 @@ impl MempoolClient for MempoolClientImpl {
             _ => Err(MempoolClientError::ClientError(ClientError::UnexpectedResponse)),
         }
     }
+
+    async fn get_txs_with_budget(
+        &self,
+        budget: ResourceBudget,
+    ) -> MempoolClientResult<Vec<ThinTransaction>> {
+        let request = MempoolRequest::GetTransactionsWithBudget(budget);
+        let response = self.send(request).await;
+        match response {
+            MempoolResponse::GetTransactionsWithBudget(Ok(response)) => Ok(response),
+            MempoolResponse::GetTransactionsWithBudget(Err(response)) => {
+                Err(MempoolClientError::MempoolError(response))
+            }
+            _ => Err(MempoolClientError::ClientError(ClientError::UnexpectedResponse)),
+        }
+    }
+
+    async fn return_txs(&self, txs: Vec<ThinTransaction>) -> MempoolClientResult<()> {
+        let request = MempoolRequest::ReturnTransactions(txs);
+        let response = self.send(request).await;
+        match response {
+            MempoolResponse::ReturnTransactions(Ok(response)) => Ok(response),
+            MempoolResponse::ReturnTransactions(Err(response)) => {
+                Err(MempoolClientError::MempoolError(response))
+            }
+            _ => Err(MempoolClientError::ClientError(ClientError::UnexpectedResponse)),
+        }
+    }
+
+    async fn commit_block(&self, args: CommitBlockArgs) -> MempoolClientResult<()> {
+        let request = MempoolRequest::CommitBlock(args);
+        let response = self.send(request).await;
+        match response {
+            MempoolResponse::CommitBlock(Ok(response)) => Ok(response),
+            MempoolResponse::CommitBlock(Err(response)) => {
+                Err(MempoolClientError::MempoolError(response))
+            }
+            _ => Err(MempoolClientError::ClientError(ClientError::UnexpectedResponse)),
+        }
+    }
+
+    async fn get_tx_status(
+        &self,
+        tx_hash: TransactionHash,
+    ) -> MempoolClientResult<TransactionStatus> {
+        let request = MempoolRequest::GetTransactionStatus(tx_hash);
+        let response = self.send(request).await;
+        match response {
+            MempoolResponse::GetTransactionStatus(Ok(response)) => Ok(response),
+            MempoolResponse::GetTransactionStatus(Err(response)) => {
+                Err(MempoolClientError::MempoolError(response))
+            }
+            _ => Err(MempoolClientError::ClientError(ClientError::UnexpectedResponse)),
+        }
+    }
+
+    async fn subscribe(&self) -> MempoolClientResult<broadcast::Receiver<MempoolEvent>> {
+        let request = MempoolRequest::Subscribe;
+        let response = self.send(request).await;
+        match response {
+            MempoolResponse::Subscribe(Ok(response)) => Ok(response),
+            MempoolResponse::Subscribe(Err(response)) => {
+                Err(MempoolClientError::MempoolError(response))
+            }
+            _ => Err(MempoolClientError::ClientError(ClientError::UnexpectedResponse)),
+        }
+    }
+
+    async fn get_metrics(&self) -> MempoolClientResult<String> {
+        let request = MempoolRequest::GetMetrics;
+        let response = self.send(request).await;
+        match response {
+            MempoolResponse::GetMetrics(Ok(response)) => Ok(response),
+            MempoolResponse::GetMetrics(Err(response)) => {
+                Err(MempoolClientError::MempoolError(response))
+            }
+            _ => Err(MempoolClientError::ClientError(ClientError::UnexpectedResponse)),
+        }
+    }
+
+    async fn is_alive(&self) -> MempoolClientResult<()> {
+        let request = MempoolRequest::IsAlive;
+        let response = self.send(request).await;
+        match response {
+            MempoolResponse::IsAlive(Ok(response)) => Ok(response),
+            MempoolResponse::IsAlive(Err(response)) => {
+                Err(MempoolClientError::MempoolError(response))
+            }
+            _ => Err(MempoolClientError::ClientError(ClientError::UnexpectedResponse)),
+        }
+    }
 }
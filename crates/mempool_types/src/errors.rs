@@ -1,3 +1,4 @@
+use starknet_api::core::Nonce;
 use starknet_api::transaction::TransactionHash;
 use thiserror::Error;
 
@@ -5,6 +6,46 @@ use thiserror::Error;
 pub enum MempoolError {
     #[error("Duplicate transaction, with hash: {tx_hash}")]
     DuplicateTransaction { tx_hash: TransactionHash },
+    #[error(
+        "Insufficient balance: the account of transaction with hash: {tx_hash} cannot cover its \
+         maximal fee, along with those of its other transactions in the mempool"
+    )]
+    InsufficientBalance { tx_hash: TransactionHash },
+    #[error(
+        "Transaction with hash: {tx_hash} does not sufficiently escalate the fees of the existing \
+         transaction with hash: {existing_tx_hash}"
+    )]
+    InsufficientFeeEscalation { tx_hash: TransactionHash, existing_tx_hash: TransactionHash },
+    #[error(
+        "Transaction with hash: {tx_hash} depends on a deploy account transaction of its sender, \
+         which is not pending in the mempool"
+    )]
+    MissingDeployAccount { tx_hash: TransactionHash },
+    #[error(
+        "Mempool is full; transaction with hash: {tx_hash} does not outbid the lowest-priority \
+         transactions"
+    )]
+    MempoolFull { tx_hash: TransactionHash },
+    #[error(
+        "Transaction with hash: {tx_hash} has nonce: {tx_nonce:?}, which is too far ahead of the \
+         account's nonce: {account_nonce:?}"
+    )]
+    NonceTooFarAhead { tx_hash: TransactionHash, tx_nonce: Nonce, account_nonce: Nonce },
+    #[error(
+        "Transaction with hash: {tx_hash} has nonce: {tx_nonce:?}, which is lower than the next \
+         expected nonce: {account_nonce:?}"
+    )]
+    NonceTooOld { tx_hash: TransactionHash, tx_nonce: Nonce, account_nonce: Nonce },
+    #[error(
+        "Staged transaction with hash: {tx_hash} must be returned along with the preceding \
+         transactions of its account"
+    )]
+    StagedTransactionNotReturned { tx_hash: TransactionHash },
+    #[error(
+        "The account of transaction with hash: {tx_hash} reached the limit of \
+         {max_txs_per_account} transactions in the mempool"
+    )]
+    TooManyAccountTransactions { tx_hash: TransactionHash, max_txs_per_account: usize },
     #[error("Transaction with hash: {tx_hash} not found")]
     TransactionNotFound { tx_hash: TransactionHash },
     // TODO(Mohammad): Consider using `StarknetApiError` once it implements `PartialEq`.
@@ -1,33 +1,179 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use cairo_lang_starknet_classes::casm_contract_class::CasmContractClass;
+use serde::{Deserialize, Serialize};
 use starknet_api::core::{ContractAddress, Nonce};
+use starknet_api::rpc_transaction::{
+    RPCDeclareTransaction, RPCDeployAccountTransaction, RPCInvokeTransaction, RPCTransaction,
+    ResourceBoundsMapping,
+};
 use starknet_api::transaction::{Tip, TransactionHash};
 
 use crate::errors::MempoolError;
 
-#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct ThinTransaction {
     pub sender_address: ContractAddress,
     pub tx_hash: TransactionHash,
     pub tip: Tip,
     pub nonce: Nonce,
+    pub resource_bounds: ResourceBoundsMapping,
+    // The full transaction, as validated by the gateway. Shared, as it may be large (e.g., a
+    // declared class), and is held in the mempool only once.
+    pub executable_tx: Arc<ExecutableTransaction>,
+}
+
+impl ThinTransaction {
+    pub fn is_deploy_account(&self) -> bool {
+        matches!(self.executable_tx.tx, RPCTransaction::DeployAccount(_))
+    }
+}
+
+/// A transaction as validated by the gateway, holding everything needed to execute it.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ExecutableTransaction {
+    pub tx: RPCTransaction,
+    // The compiled class of a declare transaction; `None` for other transaction types.
+    pub compiled_class_info: Option<CompiledClassInfo>,
+}
+
+impl ExecutableTransaction {
+    /// Estimates the size of the transaction in bytes, dominated by the felts it carries and by
+    /// the declared class, if any.
+    pub fn estimated_size(&self) -> usize {
+        const FELT_SIZE_IN_BYTES: usize = 32;
+        // An entry point holds a selector and a function index.
+        const ENTRY_POINT_SIZE_IN_BYTES: usize = 2 * FELT_SIZE_IN_BYTES;
+
+        let (n_felts, n_class_bytes) = match &self.tx {
+            RPCTransaction::Declare(RPCDeclareTransaction::V3(tx)) => {
+                let contract_class = &tx.contract_class;
+                let entry_points = &contract_class.entry_points_by_type;
+                let n_entry_points = entry_points.constructor.len()
+                    + entry_points.external.len()
+                    + entry_points.l1handler.len();
+                (
+                    tx.signature.0.len()
+                        + tx.paymaster_data.0.len()
+                        + tx.account_deployment_data.0.len()
+                        + contract_class.sierra_program.len(),
+                    contract_class.abi.len()
+                        + contract_class.contract_class_version.len()
+                        + n_entry_points * ENTRY_POINT_SIZE_IN_BYTES,
+                )
+            }
+            RPCTransaction::DeployAccount(RPCDeployAccountTransaction::V3(tx)) => (
+                tx.signature.0.len() + tx.paymaster_data.0.len() + tx.constructor_calldata.0.len(),
+                0,
+            ),
+            RPCTransaction::Invoke(RPCInvokeTransaction::V3(tx)) => (
+                tx.signature.0.len()
+                    + tx.paymaster_data.0.len()
+                    + tx.account_deployment_data.0.len()
+                    + tx.calldata.0.len(),
+                0,
+            ),
+        };
+        let n_compiled_felts = self
+            .compiled_class_info
+            .as_ref()
+            .map_or(0, |class_info| class_info.casm_contract_class.bytecode.len());
+
+        std::mem::size_of::<Self>()
+            + (n_felts + n_compiled_felts) * FELT_SIZE_IN_BYTES
+            + n_class_bytes
+    }
+}
+
+/// The class declared by a declare transaction, compiled to Casm by the gateway.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CompiledClassInfo {
+    pub casm_contract_class: CasmContractClass,
+    pub sierra_program_length: usize,
+    pub abi_length: usize,
 }
 
-#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
 pub struct AccountState {
     pub nonce: Nonce,
-    // TODO: add balance field when needed.
+    // The fee token balance of the account; balances exceeding 128 bits are capped.
+    pub balance: u128,
 }
 
-#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
 pub struct Account {
     // TODO(Ayelet): Consider removing this field as it is duplicated in ThinTransaction.
     pub sender_address: ContractAddress,
     pub state: AccountState,
 }
 
-#[derive(Clone, Debug, Default, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct MempoolInput {
     pub tx: ThinTransaction,
     pub account: Account,
+    // Whether the transaction was validated assuming its account is deployed by a deploy account
+    // transaction that is not yet executed; the mempool requires the latter to be pending.
+    pub depends_on_deploy_account: bool,
+}
+
+/// The amount of each resource available for the transactions retrieved from the mempool.
+/// Data gas is bounded by the L1 gas resource bounds of transactions, and is thus covered by the
+/// L1 gas budget.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ResourceBudget {
+    pub l1_gas: u64,
+    pub l2_gas: u64,
+}
+
+/// Describes a committed block, from the mempool's perspective.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CommitBlockArgs {
+    // The state of each account whose nonce was advanced by the block.
+    pub state_changes: HashMap<ContractAddress, AccountState>,
+    // Transactions that were included in the block.
+    pub included_tx_hashes: HashSet<TransactionHash>,
+    // Transactions that were retrieved from the mempool, but rejected while building the block.
+    pub rejected_tx_hashes: HashSet<TransactionHash>,
+}
+
+/// The status of a transaction submitted to the mempool.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum TransactionStatus {
+    /// Held in the mempool, waiting for a nonce gap of its account to be filled.
+    Pending,
+    /// Eligible for sequencing.
+    Queued,
+    /// Handed out for sequencing, and not yet reported as included or rejected.
+    Staged,
+    /// Included in a committed block.
+    Included,
+    /// Rejected while building a block.
+    Rejected,
+    /// Removed from the mempool without being sequenced (e.g., due to capacity, expiry or
+    /// replacement).
+    Evicted,
+}
+
+/// A change in the mempool's transactions, broadcast to the mempool's subscribers.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum MempoolEvent {
+    /// A transaction was added to the mempool.
+    TransactionAdded(ThinTransaction),
+    /// A transaction was replaced by a transaction with the same sender and nonce, and higher
+    /// fees.
+    TransactionReplaced { replaced_tx_hash: TransactionHash, tx_hash: TransactionHash },
+    /// A transaction was removed from the mempool without being sequenced.
+    TransactionEvicted(TransactionHash),
+    /// A transaction was handed out for sequencing.
+    TransactionStaged(TransactionHash),
+    /// A transaction that was handed out for sequencing, or included in a reverted block, was
+    /// returned to the mempool.
+    TransactionReturned(TransactionHash),
+    /// A transaction was included in a committed block.
+    TransactionIncluded(TransactionHash),
+    /// A transaction was rejected while building a block.
+    TransactionRejected(TransactionHash),
 }
 
 pub type MempoolResult<T> = Result<T, MempoolError>;
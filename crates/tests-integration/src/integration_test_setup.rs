@@ -10,7 +10,7 @@ use starknet_mempool_infra::trace_util::configure_tracing;
 use starknet_mempool_node::communication::{create_node_channels, create_node_clients};
 use starknet_mempool_node::components::create_components;
 use starknet_mempool_node::servers::{create_servers, get_server_future};
-use starknet_mempool_types::mempool_types::ThinTransaction;
+use starknet_mempool_types::mempool_types::{CommitBlockArgs, ThinTransaction};
 use starknet_task_executor::tokio_executor::TokioExecutor;
 use tokio::runtime::Handle;
 use tokio::task::JoinHandle;
@@ -94,4 +94,8 @@ impl IntegrationTestSetup {
     pub async fn get_txs(&self, n_txs: usize) -> Vec<ThinTransaction> {
         self.batcher.get_txs(n_txs).await
     }
+
+    pub async fn commit_block(&self, args: CommitBlockArgs) {
+        self.batcher.commit_block(args).await
+    }
 }
@@ -13,6 +13,7 @@ use starknet_gateway::config::{
     StatelessTransactionValidatorConfig,
 };
 use starknet_gateway::errors::GatewayError;
+use starknet_gateway::gateway::AddTransactionResponse;
 use starknet_mempool_node::config::MempoolNodeConfig;
 use tokio::net::TcpListener;
 
@@ -36,6 +37,7 @@ async fn create_gateway_config() -> GatewayConfig {
         stateless_tx_validator_config,
         stateful_tx_validator_config,
         compiler_config: gateway_compiler_config,
+        admission_control_config: Default::default(),
     }
 }
 
@@ -61,7 +63,7 @@ impl GatewayClient {
         let response = self.add_tx(tx).await;
         assert!(response.status().is_success());
 
-        response.json().await.unwrap()
+        response.json::<AddTransactionResponse>().await.unwrap().tx_hash()
     }
 
     // TODO: implement when usage eventually arises.
@@ -1,5 +1,5 @@
 use starknet_mempool_types::communication::SharedMempoolClient;
-use starknet_mempool_types::mempool_types::ThinTransaction;
+use starknet_mempool_types::mempool_types::{CommitBlockArgs, ThinTransaction};
 
 pub struct MockBatcher {
     mempool_client: SharedMempoolClient,
@@ -13,4 +13,8 @@ impl MockBatcher {
     pub async fn get_txs(&self, n_txs: usize) -> Vec<ThinTransaction> {
         self.mempool_client.get_txs(n_txs).await.unwrap()
     }
+
+    pub async fn commit_block(&self, args: CommitBlockArgs) {
+        self.mempool_client.commit_block(args).await.unwrap()
+    }
 }
@@ -1,7 +1,16 @@
+use std::collections::{HashMap, HashSet};
+
 use blockifier::test_utils::contracts::FeatureContract;
 use blockifier::test_utils::CairoVersion;
+use mempool_test_utils::invoke_tx_args;
+use mempool_test_utils::starknet_api_test_utils::{
+    deployed_account_contract_address, executable_resource_bounds_mapping,
+};
+use starknet_api::core::Nonce;
 use starknet_api::transaction::TransactionHash;
 use starknet_mempool_integration_tests::integration_test_utils::setup_with_tx_generation;
+use starknet_mempool_types::mempool_types::{AccountState, CommitBlockArgs};
+use starknet_types_core::felt::Felt;
 
 #[tokio::test]
 async fn test_end_to_end() {
@@ -12,36 +21,116 @@ async fn test_end_to_end() {
     ];
     let (mock_running_system, mut tx_generator) = setup_with_tx_generation(&accounts).await;
 
-    let account0_deploy_nonce0 = &tx_generator.account_with_id(0).generate_default_deploy_account();
+    let account0_invoke_nonce0 = tx_generator.account_with_id(0).generate_default_invoke();
     let account0_invoke_nonce1 = tx_generator.account_with_id(0).generate_default_invoke();
     let account1_invoke_nonce0 = tx_generator.account_with_id(1).generate_default_invoke();
     let account0_invoke_nonce2 = tx_generator.account_with_id(0).generate_default_invoke();
+    // Never added, leaving a nonce gap for account 1.
+    let _account1_invoke_nonce1 = tx_generator.account_with_id(1).generate_default_invoke();
+    let account1_invoke_nonce2 = tx_generator.account_with_id(1).generate_default_invoke();
 
     // Test.
 
-    let account0_deploy_nonce0_tx_hash =
-        mock_running_system.assert_add_tx_success(account0_deploy_nonce0).await;
+    let account0_invoke_nonce0_tx_hash =
+        mock_running_system.assert_add_tx_success(&account0_invoke_nonce0).await;
 
-    mock_running_system.assert_add_tx_success(&account0_invoke_nonce1).await;
+    let account0_invoke_nonce1_tx_hash =
+        mock_running_system.assert_add_tx_success(&account0_invoke_nonce1).await;
 
-    // FIXME: invoke with nonce0 shouldn't be possible, fix it, make this FAIL.
     let account1_invoke_nonce0_tx_hash =
         mock_running_system.assert_add_tx_success(&account1_invoke_nonce0).await;
 
-    mock_running_system.assert_add_tx_success(&account0_invoke_nonce2).await;
+    let account0_invoke_nonce2_tx_hash =
+        mock_running_system.assert_add_tx_success(&account0_invoke_nonce2).await;
 
-    let mempool_txs = mock_running_system.get_txs(4).await;
+    mock_running_system.assert_add_tx_success(&account1_invoke_nonce2).await;
+
+    let mempool_txs = mock_running_system.get_txs(5).await;
 
     // Assert.
 
-    // Only the transactions with nonce 0 should be returned from the mempool,
-    // because we haven't merged queue-replenishment yet.
-    let expected_tx_hashes_from_get_txs =
-        [account1_invoke_nonce0_tx_hash, account0_deploy_nonce0_tx_hash];
+    // The transactions with nonce 0 are queued first, ordered by their priority (which is
+    // determined by their hashes, as their tips are equal). Whenever a transaction of account 0
+    // is retrieved, its next nonce replenishes the queue, so all of its transactions are
+    // returned. Account 1's transaction with nonce 2 is held back due to the nonce gap.
+    let actual_tx_hashes: Vec<TransactionHash> = mempool_txs.iter().map(|tx| tx.tx_hash).collect();
+    assert_eq!(actual_tx_hashes.len(), 4);
+    assert_eq!(
+        HashSet::from([actual_tx_hashes[0], actual_tx_hashes[1]]),
+        HashSet::from([account0_invoke_nonce0_tx_hash, account1_invoke_nonce0_tx_hash])
+    );
+    assert_eq!(
+        actual_tx_hashes[2..],
+        [account0_invoke_nonce1_tx_hash, account0_invoke_nonce2_tx_hash]
+    );
+}
+
+#[tokio::test]
+async fn test_deploy_account_and_invoke() {
+    // Setup.
+    let accounts = [FeatureContract::AccountWithoutValidations(CairoVersion::Cairo1)];
+    let (mock_running_system, mut tx_generator) = setup_with_tx_generation(&accounts).await;
+
+    let account0_deploy_nonce0 = tx_generator.account_with_id(0).generate_default_deploy_account();
+    // Sent by the account to be deployed, which is funded in advance.
+    let deployed_address = deployed_account_contract_address(&account0_deploy_nonce0);
+    let account0_invoke_nonce1 = tx_generator.account_with_id(0).generate_raw(invoke_tx_args!(
+        sender_address: deployed_address,
+        nonce: Nonce(Felt::ONE),
+        resource_bounds: executable_resource_bounds_mapping(),
+    ));
+
+    // Test.
+    let account0_deploy_nonce0_tx_hash =
+        mock_running_system.assert_add_tx_success(&account0_deploy_nonce0).await;
+    let account0_invoke_nonce1_tx_hash =
+        mock_running_system.assert_add_tx_success(&account0_invoke_nonce1).await;
+
+    let mempool_txs = mock_running_system.get_txs(2).await;
+
+    // Assert: the invoke transaction follows the deploy account transaction it depends on.
+    let actual_tx_hashes: Vec<TransactionHash> = mempool_txs.iter().map(|tx| tx.tx_hash).collect();
+    assert_eq!(actual_tx_hashes, [account0_deploy_nonce0_tx_hash, account0_invoke_nonce1_tx_hash]);
+}
+
+#[tokio::test]
+async fn test_commit_block() {
+    // Setup.
+    let accounts = [FeatureContract::AccountWithoutValidations(CairoVersion::Cairo1)];
+    let (mock_running_system, mut tx_generator) = setup_with_tx_generation(&accounts).await;
+
+    let account0_address = tx_generator.account_with_id(0).sender_address();
+    let account0_invoke_nonce0 = tx_generator.account_with_id(0).generate_default_invoke();
+    let account0_invoke_nonce1 = tx_generator.account_with_id(0).generate_default_invoke();
+    let account0_invoke_nonce2 = tx_generator.account_with_id(0).generate_default_invoke();
+
+    let account0_invoke_nonce0_tx_hash =
+        mock_running_system.assert_add_tx_success(&account0_invoke_nonce0).await;
+    let account0_invoke_nonce2_tx_hash =
+        mock_running_system.assert_add_tx_success(&account0_invoke_nonce2).await;
+
+    let mempool_txs = mock_running_system.get_txs(3).await;
+    let actual_tx_hashes: Vec<TransactionHash> = mempool_txs.iter().map(|tx| tx.tx_hash).collect();
+    assert_eq!(actual_tx_hashes, [account0_invoke_nonce0_tx_hash]);
+
+    // Test.
+    mock_running_system
+        .commit_block(CommitBlockArgs {
+            state_changes: HashMap::from([(
+                account0_address,
+                AccountState { nonce: Nonce(Felt::ZERO), ..Default::default() },
+            )]),
+            included_tx_hashes: HashSet::from([account0_invoke_nonce0_tx_hash]),
+            ..Default::default()
+        })
+        .await;
+
+    let account0_invoke_nonce1_tx_hash =
+        mock_running_system.assert_add_tx_success(&account0_invoke_nonce1).await;
+
+    let mempool_txs = mock_running_system.get_txs(3).await;
 
-    // This assert should be replaced with 4 once queue-replenishment is merged, also add a tx hole
-    // at that point, and ensure the assert doesn't change due to that.
-    assert_eq!(mempool_txs.len(), 2);
+    // Assert: filling the nonce gap makes the rest of the account's transactions eligible.
     let actual_tx_hashes: Vec<TransactionHash> = mempool_txs.iter().map(|tx| tx.tx_hash).collect();
-    assert_eq!(expected_tx_hashes_from_get_txs, *actual_tx_hashes);
+    assert_eq!(actual_tx_hashes, [account0_invoke_nonce1_tx_hash, account0_invoke_nonce2_tx_hash]);
 }